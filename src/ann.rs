@@ -0,0 +1,574 @@
+use crate::db::Database;
+use crate::vector;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Neighbours kept per node on the upper layers.
+const M: usize = 16;
+/// Neighbours kept per node on layer 0.
+const M0: usize = 2 * M;
+/// Candidate list size while inserting.
+const EF_CONSTRUCTION: usize = 100;
+const MAGIC: &[u8; 8] = b"TXHNSW01";
+
+/// Approximate nearest-neighbour index (HNSW) over unit-normalized embeddings.
+///
+/// Distances are cosine distances (`1 - dot`). The index only proposes
+/// candidates; callers re-score them exactly in SQL with the configured metric.
+pub struct HnswIndex {
+    dims: usize,
+    ids: Vec<i64>,
+    /// Node vectors, `dims` values each, normalized.
+    vectors: Vec<f32>,
+    /// `links[node][layer]` holds the neighbours of `node` on `layer`.
+    links: Vec<Vec<Vec<u32>>>,
+    positions: HashMap<i64, u32>,
+    entry: Option<u32>,
+    rng: u64,
+}
+
+/// Candidate ordered by distance; `BinaryHeap` pops the farthest first.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    dist: f32,
+    node: u32,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.total_cmp(&other.dist).then(self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl HnswIndex {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims,
+            ids: Vec::new(),
+            vectors: Vec::new(),
+            links: Vec::new(),
+            positions: HashMap::new(),
+            entry: None,
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Build an index from `(identifier, embedding)` pairs. Embeddings are
+    /// truncated to `dims`; shorter ones are skipped.
+    pub fn build(dims: usize, items: impl IntoIterator<Item = (i64, Vec<f32>)>) -> Self {
+        let mut index = Self::new(dims);
+        for (id, v) in items {
+            index.insert(id, &v);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Embedding prefix length the index was built over.
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// The stored (normalized) vector of an indexed identifier.
+    pub fn vector_of(&self, id: i64) -> Option<&[f32]> {
+        self.positions.get(&id).map(|&n| self.vector(n))
+    }
+
+    fn vector(&self, node: u32) -> &[f32] {
+        let start = node as usize * self.dims;
+        &self.vectors[start..start + self.dims]
+    }
+
+    fn distance(&self, a: &[f32], node: u32) -> f32 {
+        1.0 - vector::dot(a, self.vector(node))
+    }
+
+    /// Random level with the usual exponential distribution (mL = 1/ln M).
+    fn random_level(&mut self) -> usize {
+        // xorshift64*: deterministic so rebuilt indexes are identical.
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let r = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        let u = ((r >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-u.ln() / (M as f64).ln()) as usize
+    }
+
+    pub fn insert(&mut self, id: i64, embedding: &[f32]) {
+        if embedding.len() < self.dims || self.positions.contains_key(&id) {
+            return;
+        }
+        let mut v = embedding[..self.dims].to_vec();
+        vector::normalize(&mut v);
+
+        let node = self.ids.len() as u32;
+        let level = self.random_level();
+        self.ids.push(id);
+        self.vectors.extend_from_slice(&v);
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.positions.insert(id, node);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return;
+        };
+        let top = self.links[entry as usize].len() - 1;
+        let mut nearest = Candidate { dist: self.distance(&v, entry), node: entry };
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(&v, nearest, layer);
+        }
+
+        let mut entry_points = vec![nearest];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&v, &entry_points, EF_CONSTRUCTION, layer);
+            let max = if layer == 0 { M0 } else { M };
+            let neighbours: Vec<u32> = found.iter().take(M).map(|c| c.node).collect();
+            self.links[node as usize][layer] = neighbours.clone();
+            for n in neighbours {
+                self.links[n as usize][layer].push(node);
+                if self.links[n as usize][layer].len() > max {
+                    self.prune(n, layer, max);
+                }
+            }
+            entry_points = found;
+        }
+        if level > top {
+            self.entry = Some(node);
+        }
+    }
+
+    /// Keep only the `max` closest neighbours of `node` on `layer`.
+    fn prune(&mut self, node: u32, layer: usize, max: usize) {
+        let base = self.vector(node).to_vec();
+        let mut scored: Vec<Candidate> = self.links[node as usize][layer]
+            .iter()
+            .map(|&n| Candidate { dist: self.distance(&base, n), node: n })
+            .collect();
+        scored.sort();
+        self.links[node as usize][layer] = scored.into_iter().take(max).map(|c| c.node).collect();
+    }
+
+    fn greedy(&self, q: &[f32], mut best: Candidate, layer: usize) -> Candidate {
+        loop {
+            let mut improved = false;
+            for &n in &self.links[best.node as usize][layer] {
+                let dist = self.distance(q, n);
+                if dist < best.dist {
+                    best = Candidate { dist, node: n };
+                    improved = true;
+                }
+            }
+            if !improved {
+                return best;
+            }
+        }
+    }
+
+    /// Best-first search on one layer; returns up to `ef` candidates, closest first.
+    fn search_layer(&self, q: &[f32], entry_points: &[Candidate], ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<u32> = entry_points.iter().map(|c| c.node).collect();
+        let mut frontier: BinaryHeap<std::cmp::Reverse<Candidate>> =
+            entry_points.iter().copied().map(std::cmp::Reverse).collect();
+        let mut results: BinaryHeap<Candidate> = entry_points.iter().copied().collect();
+
+        while let Some(std::cmp::Reverse(current)) = frontier.pop() {
+            let worst = results.peek().map(|c| c.dist).unwrap_or(f32::MAX);
+            if current.dist > worst && results.len() >= ef {
+                break;
+            }
+            for &n in &self.links[current.node as usize][layer] {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let dist = self.distance(q, n);
+                let worst = results.peek().map(|c| c.dist).unwrap_or(f32::MAX);
+                if results.len() < ef || dist < worst {
+                    let candidate = Candidate { dist, node: n };
+                    frontier.push(std::cmp::Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    /// Approximate `k` nearest identifiers to `query` with their cosine distances.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(i64, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        if query.len() < self.dims || k == 0 {
+            return Vec::new();
+        }
+        let mut q = query[..self.dims].to_vec();
+        vector::normalize(&mut q);
+
+        let mut nearest = Candidate { dist: self.distance(&q, entry), node: entry };
+        for layer in (1..self.links[entry as usize].len()).rev() {
+            nearest = self.greedy(&q, nearest, layer);
+        }
+        self.search_layer(&q, &[nearest], k.max(EF_CONSTRUCTION / 2), 0)
+            .into_iter()
+            .take(k)
+            .map(|c| (self.ids[c.node as usize], c.dist))
+            .collect()
+    }
+
+    /// The user cache directory, where indexes are kept between sessions.
+    pub fn cache_dir() -> Option<PathBuf> {
+        directories::ProjectDirs::from("com", "rocketrecap", "transcript-explorer")
+            .map(|dirs| dirs.cache_dir().to_path_buf())
+    }
+
+    /// Cache file in `dir` for an index over `dims`-long prefixes of a
+    /// database with the given fingerprint.
+    ///
+    /// The transcript database is often a temporary file decrypted from an
+    /// `.age` export, so the cache is keyed by content rather than by path.
+    pub fn cache_path(dir: &Path, dims: usize, fingerprint: &str) -> PathBuf {
+        dir.join(format!("ann-cos-{}-{}.hnsw", dims, fingerprint))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+        out.write_all(MAGIC)?;
+        write_u32(&mut out, self.dims as u32)?;
+        write_u32(&mut out, self.ids.len() as u32)?;
+        write_u32(&mut out, self.entry.unwrap_or(u32::MAX))?;
+        for id in &self.ids {
+            out.write_all(&id.to_le_bytes())?;
+        }
+        out.write_all(&vector::encode_embedding(&self.vectors))?;
+        for layers in &self.links {
+            write_u32(&mut out, layers.len() as u32)?;
+            for neighbours in layers {
+                write_u32(&mut out, neighbours.len() as u32)?;
+                for n in neighbours {
+                    write_u32(&mut out, *n)?;
+                }
+            }
+        }
+        out.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut input = io::BufReader::new(file);
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an index file"));
+        }
+        let dims = read_u32(&mut input)? as usize;
+        let count = read_u32(&mut input)? as usize;
+        let entry = read_u32(&mut input)?;
+
+        // Identifiers, vectors and at least a layer count per node must fit
+        // in the file before anything is allocated from the header.
+        let needed = (count as u64)
+            .checked_mul(dims as u64 * 4 + 8 + 4)
+            .and_then(|body| body.checked_add(MAGIC.len() as u64 + 12));
+        if needed.is_none_or(|needed| needed > file_len) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated index file"));
+        }
+
+        let mut index = Self::new(dims);
+        for node in 0..count {
+            let mut buf = [0u8; 8];
+            input.read_exact(&mut buf)?;
+            let id = i64::from_le_bytes(buf);
+            index.ids.push(id);
+            index.positions.insert(id, node as u32);
+        }
+        let mut raw = vec![0u8; count * dims * 4];
+        input.read_exact(&mut raw)?;
+        index.vectors = vector::decode_embedding(&raw, count * dims);
+        let corrupt = || io::Error::new(io::ErrorKind::InvalidData, "corrupt index file");
+        for _ in 0..count {
+            let layers = read_u32(&mut input)? as usize;
+            if layers == 0 {
+                return Err(corrupt());
+            }
+            let mut node_links = Vec::new();
+            for _ in 0..layers {
+                let n = read_u32(&mut input)? as usize;
+                let neighbours = (0..n).map(|_| read_u32(&mut input)).collect::<io::Result<Vec<u32>>>()?;
+                if neighbours.iter().any(|&x| x as usize >= count) {
+                    return Err(corrupt());
+                }
+                node_links.push(neighbours);
+            }
+            index.links.push(node_links);
+        }
+        // Searches index `links[n][layer]` for every neighbour `n` found on `layer`
+        for node_links in &index.links {
+            for (layer, neighbours) in node_links.iter().enumerate() {
+                if neighbours.iter().any(|&n| index.links[n as usize].len() <= layer) {
+                    return Err(corrupt());
+                }
+            }
+        }
+        if count > 0 && (entry as usize) >= count {
+            return Err(corrupt());
+        }
+        index.entry = (count > 0).then_some(entry);
+        Ok(index)
+    }
+}
+
+/// Handle to an index being loaded from cache or built in the background.
+///
+/// Dropping the handle aborts the task.
+pub struct IndexBuild {
+    rx: std::sync::mpsc::Receiver<Result<HnswIndex, String>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl IndexBuild {
+    /// Start loading (or building and caching) the index over the
+    /// `dims`-long embedding prefixes, on a separate connection.
+    pub fn spawn(db: &Database, dims: usize) -> turso::Result<Self> {
+        let reader = db.reader()?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let task = tokio::spawn(async move {
+            let _ = tx.send(load_or_build(reader, dims).await);
+        });
+        Ok(Self { rx, task })
+    }
+
+    /// The finished index (or error), once available.
+    pub fn poll(&self) -> Option<Result<HnswIndex, String>> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for IndexBuild {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Load the cosine index over `dims`-long prefixes for `db` from the cache,
+/// or build it and cache it.
+pub async fn load_or_build(db: Database, dims: usize) -> Result<HnswIndex, String> {
+    load_or_build_in(db, dims, HnswIndex::cache_dir()).await
+}
+
+/// `load_or_build` with the cache in `cache_dir`. Databases decoded in memory
+/// are never cached, as the index holds their embeddings and identifiers.
+async fn load_or_build_in(db: Database, dims: usize, cache_dir: Option<PathBuf>) -> Result<HnswIndex, String> {
+    let cache = match cache_dir.filter(|_| !db.is_ephemeral()) {
+        Some(dir) => {
+            let fingerprint = db.embedding_fingerprint().await.map_err(|e| e.to_string())?;
+            Some(HnswIndex::cache_path(&dir, dims, &fingerprint))
+        }
+        None => None,
+    };
+    if let Some(path) = cache.clone().filter(|p| p.exists()) {
+        let loaded = tokio::task::spawn_blocking(move || HnswIndex::load(&path))
+            .await
+            .map_err(|e| e.to_string())?;
+        // A corrupt or outdated cache file is simply rebuilt.
+        if let Some(index) = loaded.ok().filter(|index| index.dims() == dims) {
+            return Ok(index);
+        }
+    }
+
+    let items = db.all_embeddings(dims).await.map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        let index = HnswIndex::build(dims, items);
+        if let Some(path) = cache {
+            let _ = index.save(&path);
+        }
+        index
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+fn write_u32(out: &mut impl Write, v: u32) -> io::Result<()> {
+    out.write_all(&v.to_le_bytes())
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vectors(count: usize, dims: usize) -> Vec<(i64, Vec<f32>)> {
+        let mut state = 42u64;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+        };
+        (0..count)
+            .map(|i| (i as i64, (0..dims).map(|_| next()).collect()))
+            .collect()
+    }
+
+    fn exact(items: &[(i64, Vec<f32>)], q: &[f32], k: usize) -> Vec<i64> {
+        let mut q = q.to_vec();
+        vector::normalize(&mut q);
+        let mut scored: Vec<(f32, i64)> = items
+            .iter()
+            .map(|(id, v)| {
+                let mut v = v.clone();
+                vector::normalize(&mut v);
+                (1.0 - q.iter().zip(&v).map(|(a, b)| a * b).sum::<f32>(), *id)
+            })
+            .collect();
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.into_iter().take(k).map(|(_, id)| id).collect()
+    }
+
+    #[test]
+    fn test_hnsw_recall() {
+        let items = random_vectors(1000, 24);
+        let index = HnswIndex::build(24, items.clone());
+        assert_eq!(index.len(), 1000);
+
+        let mut found = 0;
+        for (_, q) in items.iter().step_by(50) {
+            let truth: HashSet<i64> = exact(&items, q, 10).into_iter().collect();
+            found += index.search(q, 10).iter().filter(|(id, _)| truth.contains(id)).count();
+        }
+        assert!(found >= 180, "recall too low: {}/200", found);
+    }
+
+    #[test]
+    fn test_hnsw_save_load_round_trip() {
+        let items = random_vectors(200, 8);
+        let index = HnswIndex::build(8, items.clone());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.hnsw");
+        index.save(&path).unwrap();
+
+        let loaded = HnswIndex::load(&path).unwrap();
+        assert_eq!(loaded.len(), 200);
+        assert_eq!(loaded.vector_of(7), index.vector_of(7));
+        assert_eq!(loaded.search(&items[3].1, 5), index.search(&items[3].1, 5));
+        assert_eq!(loaded.search(&items[3].1, 1)[0].0, 3);
+    }
+
+    #[test]
+    fn test_hnsw_load_rejects_inconsistent_layers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.hnsw");
+        // Two 1-dim nodes; node 0 links to node 1 on `layer` and node 1 has `layers_of_1` layers
+        let write = |entry: u32, layers_of_1: u32, layer: usize| {
+            let mut bytes = MAGIC.to_vec();
+            for v in [1u32, 2, entry] {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+            for id in [10i64, 11] {
+                bytes.extend_from_slice(&id.to_le_bytes());
+            }
+            bytes.extend_from_slice(&vector::encode_embedding(&[1.0, 1.0]));
+            let mut node0 = vec![vec![]; layer + 1];
+            node0[layer] = vec![1u32];
+            let node1 = vec![vec![0u32]; layers_of_1 as usize];
+            for node in [node0, node1] {
+                bytes.extend_from_slice(&(node.len() as u32).to_le_bytes());
+                for neighbours in node {
+                    bytes.extend_from_slice(&(neighbours.len() as u32).to_le_bytes());
+                    for n in neighbours {
+                        bytes.extend_from_slice(&n.to_le_bytes());
+                    }
+                }
+            }
+            std::fs::write(&path, bytes).unwrap();
+            HnswIndex::load(&path)
+        };
+
+        let index = write(0, 1, 0).unwrap();
+        assert_eq!(index.search(&[1.0], 2).len(), 2);
+        // Neighbour on layer 1 that only exists on layer 0
+        assert_eq!(write(0, 1, 1).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        // Node without layers
+        assert_eq!(write(0, 0, 0).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        // Entry point out of range
+        assert_eq!(write(5, 1, 0).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_hnsw_load_rejects_oversized_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.hnsw");
+        let mut bytes = MAGIC.to_vec();
+        for v in [768u32, u32::MAX - 1, 0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        std::fs::write(&path, bytes).unwrap();
+        let err = HnswIndex::load(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_database_is_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy())
+            .build()
+            .await
+            .unwrap()
+            .connect()
+            .unwrap();
+        conn.execute("CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, embedding BLOB)", ())
+            .await
+            .unwrap();
+        for (id, v) in random_vectors(20, 4) {
+            conn.execute(
+                "INSERT INTO items VALUES (?1, 's', ?2)",
+                (id, turso::Value::Blob(vector::encode_embedding(&v))),
+            )
+            .await
+            .unwrap();
+        }
+        crate::import::checkpoint(&conn).await.unwrap();
+        drop(conn);
+
+        let cache = dir.path().join("cache");
+        let image = std::fs::read(&path).unwrap();
+        let db = Database::open_image(&image).await.unwrap();
+        let index = load_or_build_in(db, 4, Some(cache.clone())).await.unwrap();
+        assert_eq!(index.len(), 20);
+        assert!(!cache.exists());
+
+        // The same data opened from a file is cached
+        let db = Database::open(&path).await.unwrap();
+        load_or_build_in(db, 4, Some(cache.clone())).await.unwrap();
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_hnsw_skips_short_embeddings() {
+        let index = HnswIndex::build(4, vec![(1, vec![1.0, 0.0]), (2, vec![1.0, 0.0, 0.0, 0.0])]);
+        assert_eq!(index.len(), 1);
+        assert!(index.vector_of(1).is_none());
+    }
+}
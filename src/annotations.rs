@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use turso::Value;
+
+/// Key that identifies a transcript across exports: its identifier together
+/// with the original source link, as stored in the database.
+pub type AnnotationKey = (i64, String);
+
+/// User annotations for one transcript.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotation {
+    pub bookmarked: bool,
+    pub tags: Vec<String>,
+    pub note: String,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        !self.bookmarked && self.tags.is_empty() && self.note.is_empty()
+    }
+}
+
+/// Split user input like `"rust, talks  #async"` into normalized tags.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|t| t.trim_start_matches('#').trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Sidecar SQLite database holding bookmarks, tags and notes.
+///
+/// It lives next to the user's data rather than next to the transcript
+/// database, because that is usually a temporary file decrypted from an
+/// `.age` export and is deleted on exit.
+pub struct AnnotationStore {
+    conn: turso::Connection,
+}
+
+impl AnnotationStore {
+    /// Default location: `<data dir>/annotations.db`.
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("com", "rocketrecap", "transcript-explorer")
+            .map(|dirs| dirs.data_dir().join("annotations.db"))
+    }
+
+    /// Open (or create) the store at `path`.
+    pub async fn open(path: &Path) -> turso::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| turso::Error::IoError(e.kind()))?;
+        }
+        let db = turso::Builder::new_local(&path.to_string_lossy()).build().await?;
+        let conn = db.connect()?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS annotations (\
+             identifier INTEGER NOT NULL, \
+             original_source_link TEXT NOT NULL, \
+             bookmarked INTEGER NOT NULL DEFAULT 0, \
+             tags TEXT NOT NULL DEFAULT '', \
+             note TEXT NOT NULL DEFAULT '', \
+             updated_at TEXT NOT NULL, \
+             PRIMARY KEY (identifier, original_source_link))",
+            (),
+        )
+        .await?;
+        Ok(Self { conn })
+    }
+
+    /// Load every annotation into memory.
+    pub async fn load_all(&self) -> turso::Result<HashMap<AnnotationKey, Annotation>> {
+        let mut map = HashMap::new();
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT identifier, original_source_link, bookmarked, tags, note FROM annotations",
+                (),
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            let identifier = match row.get_value(0)? {
+                Value::Integer(i) => i,
+                _ => continue,
+            };
+            let text = |v: Value| match v {
+                Value::Text(s) => s,
+                _ => String::new(),
+            };
+            let link = text(row.get_value(1)?);
+            let annotation = Annotation {
+                bookmarked: matches!(row.get_value(2)?, Value::Integer(i) if i != 0),
+                tags: parse_tags(&text(row.get_value(3)?)),
+                note: text(row.get_value(4)?),
+            };
+            map.insert((identifier, link), annotation);
+        }
+        Ok(map)
+    }
+
+    /// Insert, update or (when empty) delete the annotation for `key`.
+    pub async fn save(&self, key: &AnnotationKey, annotation: &Annotation) -> turso::Result<()> {
+        let (identifier, link) = key;
+        // Delete + insert rather than INSERT OR REPLACE: turso 0.4 drops bound
+        // text values on the REPLACE path for columns with a DEFAULT.
+        self.conn
+            .execute(
+                "DELETE FROM annotations WHERE identifier = ?1 AND original_source_link = ?2",
+                turso::params::Params::Positional(vec![
+                    Value::Integer(*identifier),
+                    Value::Text(link.clone()),
+                ]),
+            )
+            .await?;
+        if annotation.is_empty() {
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO annotations \
+                 (identifier, original_source_link, bookmarked, tags, note, updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                turso::params::Params::Positional(vec![
+                    Value::Integer(*identifier),
+                    Value::Text(link.clone()),
+                    Value::Integer(annotation.bookmarked as i64),
+                    Value::Text(annotation.tags.join(",")),
+                    Value::Text(annotation.note.clone()),
+                    Value::Text(chrono::Utc::now().to_rfc3339()),
+                ]),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags("Rust, talks  #async,rust"), vec!["async", "rust", "talks"]);
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AnnotationStore::open(&dir.path().join("a.db")).await.unwrap();
+        let key = (42, "https://youtu.be/x".to_string());
+        let annotation = Annotation {
+            bookmarked: true,
+            tags: vec!["rust".to_string(), "talks".to_string()],
+            note: "watch again".to_string(),
+        };
+        store.save(&key, &annotation).await.unwrap();
+        assert_eq!(store.load_all().await.unwrap().get(&key), Some(&annotation));
+
+        let updated = Annotation { note: "seen".to_string(), ..annotation };
+        store.save(&key, &updated).await.unwrap();
+        assert_eq!(store.load_all().await.unwrap().get(&key), Some(&updated));
+
+        store.save(&key, &Annotation::default()).await.unwrap();
+        assert!(store.load_all().await.unwrap().is_empty());
+    }
+}
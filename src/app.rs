@@ -1,5 +1,17 @@
-use crate::db::{Database, SimilarResult, TranscriptListItem, TranscriptRow};
-use std::collections::HashMap;
+use crate::ann::{HnswIndex, IndexBuild};
+use crate::annotations::{parse_tags, Annotation, AnnotationKey, AnnotationStore};
+use crate::db::{Database, SimilarResult, TranscriptListItem, TranscriptRow, SIMILARITY_DIMS};
+use crate::duplicates::{find_duplicates, DuplicateCluster, DEFAULT_MAX_DISTANCE};
+use crate::export::{write_export, ExportError, ExportFormat, DEFAULT_COLUMNS};
+use crate::fulltext::{FulltextBuild, FulltextIndex};
+use crate::query;
+use crate::stats::{self, breakdown, GroupBy, GroupStats, Stats};
+use crate::settings::{Settings, SettingsError};
+use crate::topics::{Topic, TopicBuild};
+use crate::transcript_search::TranscriptSearch;
+use crate::vector::Metric;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use wildmatch::WildMatch;
 
 /// Which view is currently active.
@@ -9,10 +21,13 @@ pub enum View {
     Detail,
     Similar,
     Filters, // Added new view
+    Duplicates,
+    Topics,
+    Stats,
 }
 
 /// Which tab is selected in the detail view.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum DetailTab {
     Summary,
     Transcript,
@@ -45,13 +60,38 @@ impl DetailTab {
     }
 
     pub const ALL: [DetailTab; 3] = [Self::Summary, Self::Transcript, Self::Timestamps];
+
+    /// Text of this tab for `row`; timestamps prefer the YouTube-formatted summary.
+    pub fn content(self, row: &TranscriptRow) -> &str {
+        match self {
+            Self::Summary => &row.summary,
+            Self::Transcript => &row.transcript,
+            Self::Timestamps => {
+                if !row.timestamped_summary_in_youtube_format.is_empty() {
+                    &row.timestamped_summary_in_youtube_format
+                } else {
+                    &row.timestamps
+                }
+            }
+        }
+    }
 }
 
-/// Input mode for the filter bar.
+/// Input mode for the filter bar and annotation prompts.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputMode {
     Normal,
     Editing,
+    EditingTags,
+    EditingNote,
+    EditingExportPath,
+}
+
+impl InputMode {
+    /// Whether the single-line prompt overlay is open.
+    pub fn is_prompt(&self) -> bool {
+        matches!(self, InputMode::EditingTags | InputMode::EditingNote | InputMode::EditingExportPath)
+    }
 }
 
 /// A group of consecutive identical entries.
@@ -91,16 +131,19 @@ impl Filter {
                 val >= *min && val <= *max
             }
             Filter::Match { field, pattern } => {
+                // Case-insensitive wildcard match
+                let pattern_low = pattern.to_lowercase();
+                let wildcard = WildMatch::new(&pattern_low);
                 let val = match field.as_str() {
                     "model" => &item.model,
                     "host" => &item.host,
                     "link" => &item.original_source_link,
+                    "tag" => return item.tags.iter().any(|t| wildcard.matches(t)),
+                    "bookmarked" => if item.bookmarked { "yes" } else { "no" },
                     _ => "",
                 };
-                // Case-insensitive wildcard match
-                let pattern_low = pattern.to_lowercase();
                 let val_low = val.to_lowercase();
-                WildMatch::new(&pattern_low).matches(&val_low)
+                wildcard.matches(&val_low)
             }
             Filter::And(filters) => filters.iter().all(|f| f.matches(item)),
             Filter::Or(filters) => filters.iter().any(|f| f.matches(item)),
@@ -109,21 +152,11 @@ impl Filter {
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct Stats {
-    pub count: usize,
-    pub mean: f64,
-    pub stddev: f64,
-    pub min: f64,
-    pub max: f64,
-    pub median: f64,
-    pub mad: f64,
-    pub p5: f64,
-    pub p95: f64,
-}
-
 pub const LIST_OVERHEAD: u16 = 9;
 
+/// With exact re-ranking, this many ANN candidates per requested result are re-scored.
+const RERANK_POOL: usize = 4;
+
 /// Main application state.
 pub struct App {
     pub db: Database,
@@ -134,6 +167,7 @@ pub struct App {
     // In-memory cache
     pub all_items: Vec<TranscriptListItem>,
     pub filtered_indices: Vec<usize>,
+    positions: HashMap<i64, usize>, // identifier -> index into all_items
 
     // Grouped items for the list display
     pub grouped_items: Vec<TranscriptGroup>,
@@ -147,10 +181,22 @@ pub struct App {
     pub filter: String,
     pub input_mode: InputMode,
 
+    // Background transcript-body search
+    pub search_transcripts: bool,
+    pub transcript_search: Option<TranscriptSearch>,
+
+    // Full-text index, built in the background on the first `F`
+    fulltext: Option<FulltextIndex>,
+    pub fulltext_build: Option<FulltextBuild>,
+    /// Rank the list as soon as the index is ready.
+    fulltext_pending: bool,
+
     // Detail view state
     pub detail: Option<TranscriptRow>,
     pub detail_tab: DetailTab,
     pub detail_scroll: u16,
+    /// View to return to when the detail view is closed.
+    pub detail_return: View,
 
     // Similar view state
     pub similar_results: Vec<SimilarResult>,
@@ -158,6 +204,38 @@ pub struct App {
     pub similar_selected: usize,
     pub similar_source_id: i64,
     pub similar_source_preview: String,
+    /// Seeds of a centroid search; empty for a single-source search.
+    pub similar_seed_ids: Vec<i64>,
+    /// The last page came back short, so "load more" has nothing to fetch.
+    similar_exhausted: bool,
+
+    // Approximate nearest-neighbour index, loaded or built in the background
+    pub ann: Option<HnswIndex>,
+    ann_build: Option<IndexBuild>,
+
+    // Duplicates view state; the selection indexes the members of all clusters in order
+    pub duplicate_clusters: Vec<DuplicateCluster>,
+    pub duplicates_selected: usize,
+    // Index at SIMILARITY_DIMS when `ann` uses another prefix length, so the
+    // distance threshold means the same as in the `duplicates` command
+    duplicates_ann: Option<HnswIndex>,
+    duplicates_build: Option<IndexBuild>,
+
+    // Topics view state; `topic_open` is the topic whose members are shown
+    pub topics: Vec<Topic>,
+    pub topic_labels: Vec<String>,
+    pub topics_selected: usize,
+    pub topic_open: Option<usize>,
+    pub topic_member_selected: usize,
+    topics_build: Option<TopicBuild>,
+
+    // Stats view state: breakdown of the filtered list by `stats_group_by`
+    pub stats_group_by: GroupBy,
+    pub stats_groups: Vec<GroupStats>,
+    pub stats_selected: usize,
+
+    // Entries marked with `m` as seeds for a centroid search
+    pub marked: BTreeSet<i64>,
 
     // Global filters state
     pub global_filter: Option<Filter>,
@@ -165,6 +243,22 @@ pub struct App {
     pub unique_models: Vec<String>,
     pub filter_builder_state: FilterBuilderState,
 
+    // User annotations (bookmarks, tags, notes) from the sidecar store
+    pub annotations: HashMap<AnnotationKey, Annotation>,
+    annotation_store: Option<AnnotationStore>,
+    /// Text being edited in the single-line prompt.
+    pub prompt_buffer: String,
+    annotation_target: Option<AnnotationKey>,
+
+    // File name suggested by the next list export, and an existing file the
+    // user was warned about; exporting to it again overwrites it
+    export_path: String,
+    export_overwrite: Option<String>,
+
+    // Persisted user settings
+    pub settings: Settings,
+    settings_path: Option<PathBuf>,
+
     // Status message
     pub status_msg: String,
 
@@ -195,6 +289,7 @@ impl App {
 
             all_items: Vec::new(),
             filtered_indices: Vec::new(),
+            positions: HashMap::new(),
             grouped_items: Vec::new(),
 
             list_items: Vec::new(),
@@ -205,15 +300,56 @@ impl App {
             filter: String::new(),
             input_mode: InputMode::Normal,
 
+            search_transcripts: false,
+            transcript_search: None,
+
+            fulltext: None,
+            fulltext_build: None,
+            fulltext_pending: false,
+
             detail: None,
             detail_tab: DetailTab::Summary,
             detail_scroll: 0,
+            detail_return: View::List,
 
             similar_results: Vec::new(),
             grouped_similar_results: Vec::new(),
             similar_selected: 0,
             similar_source_id: 0,
             similar_source_preview: String::new(),
+            similar_seed_ids: Vec::new(),
+            similar_exhausted: false,
+
+            ann: None,
+            ann_build: None,
+
+            duplicate_clusters: Vec::new(),
+            duplicates_selected: 0,
+            duplicates_ann: None,
+            duplicates_build: None,
+
+            topics: Vec::new(),
+            topic_labels: Vec::new(),
+            topics_selected: 0,
+            topic_open: None,
+            topic_member_selected: 0,
+            topics_build: None,
+            stats_group_by: GroupBy::Model,
+            stats_groups: Vec::new(),
+            stats_selected: 0,
+
+            marked: BTreeSet::new(),
+
+            annotations: HashMap::new(),
+            annotation_store: None,
+            prompt_buffer: String::new(),
+            annotation_target: None,
+
+            export_path: "transcripts.jsonl".to_string(),
+            export_overwrite: None,
+
+            settings: Settings::default(),
+            settings_path: None,
 
             status_msg: "Loading database...".to_string(),
             global_filter: None,
@@ -230,13 +366,218 @@ impl App {
     /// Initial data load.
     pub async fn init(&mut self) -> turso::Result<()> {
         self.all_items = self.db.list_all_transcripts().await?;
+        self.positions = self
+            .all_items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.identifier, i))
+            .collect();
+        if let Some(ref store) = self.annotation_store {
+            self.annotations = store.load_all().await?;
+            let keys: Vec<AnnotationKey> = self.annotations.keys().cloned().collect();
+            for key in keys {
+                self.sync_annotation(&key);
+            }
+        }
         self.calculate_all_stats();
         self.extract_unique_models();
         self.apply_filter();
+        if self.db.schema().has_embeddings() {
+            let dims = self.settings.similarity.dims.unwrap_or(SIMILARITY_DIMS);
+            self.ann_build = Some(IndexBuild::spawn(&self.db, dims)?);
+        }
         self.status_msg = format!("{} transcripts loaded", self.all_items.len());
+        let warnings = self.db.warnings();
+        if !warnings.is_empty() {
+            self.status_msg = format!("{} ({})", self.status_msg, warnings.join("; "));
+        }
         Ok(())
     }
 
+    /// Attach the sidecar store holding bookmarks, tags and notes.
+    pub fn set_annotation_store(&mut self, store: AnnotationStore) {
+        self.annotation_store = Some(store);
+    }
+
+    /// Key of the transcript the user is looking at in the current view.
+    pub fn selected_annotation_key(&self) -> Option<AnnotationKey> {
+        match self.view {
+            View::List => self
+                .list_items
+                .get(self.list_selected)
+                .and_then(|g| g.items.first())
+                .map(|it| (it.identifier, it.original_source_link.clone())),
+            View::Detail => self
+                .detail
+                .as_ref()
+                .map(|d| (d.identifier, d.original_source_link.clone())),
+            View::Similar => self
+                .grouped_similar_results
+                .get(self.similar_selected)
+                .and_then(|g| g.items.first())
+                .map(|it| (it.identifier, it.original_source_link.clone())),
+            View::Duplicates => self
+                .selected_duplicate()
+                .and_then(|id| self.item(id))
+                .map(|it| (it.identifier, it.original_source_link.clone())),
+            View::Topics => self
+                .selected_topic_member()
+                .and_then(|id| self.item(id))
+                .map(|it| (it.identifier, it.original_source_link.clone())),
+            View::Filters | View::Stats => None,
+        }
+    }
+
+    pub fn annotation_for(&self, identifier: i64, link: &str) -> Option<&Annotation> {
+        self.annotations.get(&(identifier, link.to_string()))
+    }
+
+    /// Toggle the bookmark on the selected transcript.
+    pub async fn toggle_bookmark(&mut self) -> turso::Result<()> {
+        let Some(key) = self.selected_annotation_key() else {
+            return Ok(());
+        };
+        let mut annotation = self.annotations.get(&key).cloned().unwrap_or_default();
+        annotation.bookmarked = !annotation.bookmarked;
+        self.status_msg = if annotation.bookmarked {
+            format!("Bookmarked {}", key.0)
+        } else {
+            format!("Removed bookmark from {}", key.0)
+        };
+        self.store_annotation(key, annotation).await
+    }
+
+    /// Open the tag or note prompt for the selected transcript, prefilled with the current value.
+    pub fn begin_annotation(&mut self, mode: InputMode) {
+        let Some(key) = self.selected_annotation_key() else {
+            return;
+        };
+        if self.annotation_store.is_none() {
+            self.status_msg = "Annotations unavailable: no annotation store".to_string();
+            return;
+        }
+        let current = self.annotations.get(&key).cloned().unwrap_or_default();
+        self.prompt_buffer = match mode {
+            InputMode::EditingTags => current.tags.join(", "),
+            InputMode::EditingNote => current.note,
+            _ => return,
+        };
+        self.annotation_target = Some(key);
+        self.input_mode = mode;
+    }
+
+    /// Save the prompt buffer as the new tags or note.
+    pub async fn commit_annotation(&mut self) -> turso::Result<()> {
+        let mode = std::mem::replace(&mut self.input_mode, InputMode::Normal);
+        let buffer = std::mem::take(&mut self.prompt_buffer);
+        let Some(key) = self.annotation_target.take() else {
+            return Ok(());
+        };
+        let mut annotation = self.annotations.get(&key).cloned().unwrap_or_default();
+        match mode {
+            InputMode::EditingTags => {
+                annotation.tags = parse_tags(&buffer);
+                self.status_msg = format!("Tags for {}: {}", key.0, annotation.tags.join(", "));
+            }
+            InputMode::EditingNote => {
+                annotation.note = buffer.trim().to_string();
+                self.status_msg = format!("Note saved for {}", key.0);
+            }
+            _ => return Ok(()),
+        }
+        self.store_annotation(key, annotation).await
+    }
+
+    /// Ask for the file to export the current list to.
+    pub fn begin_export(&mut self) {
+        self.prompt_buffer = self.export_path.clone();
+        self.export_overwrite = None;
+        self.input_mode = InputMode::EditingExportPath;
+    }
+
+    /// The file named in the export prompt exists and the next Enter overwrites it.
+    pub fn export_overwrite_pending(&self) -> bool {
+        self.export_overwrite.as_deref() == Some(self.prompt_buffer.trim())
+    }
+
+    /// Export the current (filtered) list to the file name in the prompt.
+    ///
+    /// An existing file is only overwritten when the export is confirmed a
+    /// second time; until then the prompt stays open.
+    pub async fn commit_export(&mut self) -> Result<(), ExportError> {
+        let path = self.prompt_buffer.trim().to_string();
+        if path.is_empty() {
+            self.cancel_annotation();
+            return Ok(());
+        }
+        let Some(format) = ExportFormat::from_path(Path::new(&path)) else {
+            self.status_msg = "Unknown export format: use .json, .jsonl, .csv or .md".to_string();
+            self.cancel_annotation();
+            return Ok(());
+        };
+        if Path::new(&path).exists() && !self.export_overwrite_pending() {
+            self.status_msg = format!("{} exists: press Enter again to overwrite it", path);
+            self.export_overwrite = Some(path);
+            return Ok(());
+        }
+        self.cancel_annotation();
+        self.export_overwrite = None;
+        let items: Vec<&TranscriptListItem> = self.filtered_indices.iter().map(|&i| &self.all_items[i]).collect();
+        let mut out = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        write_export(&self.db, &items, &DEFAULT_COLUMNS, format, &mut out).await?;
+        self.status_msg = format!("Exported {} entries to {}", items.len(), path);
+        self.export_path = path;
+        Ok(())
+    }
+
+    pub fn cancel_annotation(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.prompt_buffer.clear();
+        self.annotation_target = None;
+    }
+
+    async fn store_annotation(&mut self, key: AnnotationKey, annotation: Annotation) -> turso::Result<()> {
+        let Some(ref store) = self.annotation_store else {
+            self.status_msg = "Annotations unavailable: no annotation store".to_string();
+            return Ok(());
+        };
+        store.save(&key, &annotation).await?;
+        if annotation.is_empty() {
+            self.annotations.remove(&key);
+        } else {
+            self.annotations.insert(key.clone(), annotation);
+        }
+        self.sync_annotation(&key);
+        self.regroup_keep_position();
+        Ok(())
+    }
+
+    /// Copy bookmark and tags onto the cached list item so filters can see them.
+    fn sync_annotation(&mut self, key: &AnnotationKey) {
+        let Some(&idx) = self.positions.get(&key.0) else {
+            return;
+        };
+        let item = &mut self.all_items[idx];
+        if item.original_source_link != key.1 {
+            return;
+        }
+        let annotation = self.annotations.get(key).cloned().unwrap_or_default();
+        item.bookmarked = annotation.bookmarked;
+        item.tags = annotation.tags;
+    }
+
+    /// Every tag in use, sorted, for display in the filter builder.
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .annotations
+            .values()
+            .flat_map(|a| a.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
     /// Set the update channels for receiving messages and sending responses
     pub fn set_update_channels(
         &mut self,
@@ -266,68 +607,14 @@ impl App {
     }
 
     pub fn calculate_all_stats(&mut self) {
-        let fields = vec!["cost".to_string(), "input_tokens".to_string(), "output_tokens".to_string()];
-        for field in fields {
-            let values: Vec<f64> = self.all_items.iter().map(|it| {
-                match field.as_str() {
-                    "cost" => it.cost,
-                    "input_tokens" => it.summary_input_tokens as f64,
-                    "output_tokens" => it.summary_output_tokens as f64,
-                    _ => 0.0,
-                }
-            }).collect();
-            
+        for field in stats::FIELDS {
+            let values: Vec<f64> = self.all_items.iter().map(|it| stats::field_value(it, field)).collect();
             if !values.is_empty() {
-                self.field_stats.insert(field, self.calculate_stats(values));
+                self.field_stats.insert(field.to_string(), Stats::from_values(values));
             }
         }
     }
 
-    fn calculate_stats(&self, mut values: Vec<f64>) -> Stats {
-        let n = values.len();
-        if n == 0 { return Stats::default(); }
-
-        let sum: f64 = values.iter().sum();
-        let mean = sum / n as f64;
-        
-        let var_sum: f64 = values.iter().map(|&v| (v - mean).powi(2)).sum();
-        let stddev = (var_sum / n as f64).sqrt();
-        
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        
-        let min = values[0];
-        let max = values[n-1];
-        let median = if n % 2 == 1 {
-            values[n / 2]
-        } else {
-            (values[n / 2 - 1] + values[n / 2]) / 2.0
-        };
-
-        // MAD
-        let mut devs: Vec<f64> = values.iter().map(|&v| (v - median).abs()).collect();
-        devs.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let mad = if n % 2 == 1 {
-            devs[n / 2]
-        } else {
-            (devs[n / 2 - 1] + devs[n / 2]) / 2.0
-        };
-
-        let p5 = values[(n as f64 * 0.05) as usize];
-        let p95 = values[(n as f64 * 0.95).min(n as f64 - 1.0) as usize];
-
-        Stats {
-            count: n,
-            mean,
-            stddev,
-            min,
-            max,
-            median,
-            mad,
-            p5,
-            p95,
-        }
-    }
-
     pub fn add_filter(&mut self, filter: Filter) {
         if let Some(existing) = self.global_filter.take() {
             match existing {
@@ -421,36 +708,190 @@ impl App {
 
     /// Open the detail view for the currently selected item.
     pub async fn open_detail(&mut self) -> turso::Result<()> {
-        match self.view {
-            View::List => {
-                if let Some(group) = self.list_items.get(self.list_selected) {
-                    if let Some(item) = group.items.first() {
-                        let id = item.identifier;
-                        if let Some(row) = self.db.get_transcript(id).await? {
-                            self.detail = Some(row);
-                            self.detail_tab = DetailTab::Summary;
-                            self.detail_scroll = 0;
-                            self.view = View::Detail;
-                        }
-                    }
-                }
+        let id = match self.view {
+            View::List => self
+                .list_items
+                .get(self.list_selected)
+                .and_then(|group| group.items.first())
+                .map(|item| item.identifier),
+            View::Similar => self
+                .grouped_similar_results
+                .get(self.similar_selected)
+                .and_then(|group| group.items.first())
+                .map(|item| item.identifier),
+            View::Duplicates => self.selected_duplicate(),
+            View::Topics => self.selected_topic_member(),
+            _ => None,
+        };
+        if let Some(id) = id
+            && let Some(row) = self.db.get_transcript(id).await?
+        {
+            self.detail = Some(row);
+            self.detail_tab = DetailTab::Summary;
+            self.detail_scroll = 0;
+            self.detail_return = self.view.clone();
+            self.view = View::Detail;
+        }
+        Ok(())
+    }
+
+    /// Close the detail view and go back to where it was opened from.
+    pub fn close_detail(&mut self) {
+        self.view = std::mem::replace(&mut self.detail_return, View::List);
+        self.detail = None;
+    }
+
+    /// List row of a transcript by identifier.
+    pub fn item(&self, identifier: i64) -> Option<&TranscriptListItem> {
+        self.positions.get(&identifier).map(|&i| &self.all_items[i])
+    }
+
+    /// Find duplicate clusters over the whole corpus and show them.
+    ///
+    /// Embedding duplicates need an ANN index over the first
+    /// `SIMILARITY_DIMS` components, as in the `duplicates` command; until it
+    /// is ready only source links are compared.
+    pub fn open_duplicates(&mut self) -> turso::Result<()> {
+        if self.duplicates_index().is_none()
+            && self.duplicates_build.is_none()
+            && self.db.schema().has_embeddings()
+            && self.settings.similarity.dims != Some(SIMILARITY_DIMS)
+        {
+            self.duplicates_build = Some(IndexBuild::spawn(&self.db, SIMILARITY_DIMS)?);
+        }
+        self.duplicates_selected = 0;
+        self.view = View::Duplicates;
+        self.refresh_duplicates();
+        Ok(())
+    }
+
+    /// The ANN index to find embedding duplicates with, if one is ready.
+    fn duplicates_index(&self) -> Option<&HnswIndex> {
+        self.ann
+            .as_ref()
+            .filter(|index| index.dims() == SIMILARITY_DIMS)
+            .or(self.duplicates_ann.as_ref())
+    }
+
+    fn refresh_duplicates(&mut self) {
+        self.duplicate_clusters = find_duplicates(&self.all_items, self.duplicates_index(), DEFAULT_MAX_DISTANCE);
+        let total: usize = self.duplicate_clusters.iter().map(|c| c.members.len()).sum();
+        self.duplicates_selected = self.duplicates_selected.min(total.saturating_sub(1));
+        let redundant = total - self.duplicate_clusters.len();
+        self.status_msg = format!("{} duplicate clusters, {} redundant entries", self.duplicate_clusters.len(), redundant);
+        if self.duplicates_index().is_none() && self.db.schema().has_embeddings() {
+            self.status_msg.push_str(" (similarity index not ready: links only)");
+        }
+    }
+
+    /// Identifier of the selected member in the duplicates view.
+    pub fn selected_duplicate(&self) -> Option<i64> {
+        self.duplicate_clusters
+            .iter()
+            .flat_map(|c| c.members.iter())
+            .nth(self.duplicates_selected)
+            .copied()
+    }
+
+    pub fn duplicates_next(&mut self, step: usize) {
+        let total: usize = self.duplicate_clusters.iter().map(|c| c.members.len()).sum();
+        self.duplicates_selected = (self.duplicates_selected + step).min(total.saturating_sub(1));
+    }
+
+    pub fn duplicates_prev(&mut self, step: usize) {
+        self.duplicates_selected = self.duplicates_selected.saturating_sub(step);
+    }
+
+    /// Show the topics view, clustering the embeddings in the background on first use.
+    pub fn open_topics(&mut self) -> turso::Result<()> {
+        if !self.db.schema().has_embeddings() {
+            self.status_msg = "No embeddings in this database".to_string();
+            return Ok(());
+        }
+        if self.topics.is_empty() && self.topics_build.is_none() {
+            self.topics_build = Some(TopicBuild::spawn(&self.db)?);
+            self.status_msg = "Clustering embeddings into topics...".to_string();
+        }
+        self.view = View::Topics;
+        Ok(())
+    }
+
+    /// Pick up the topics once the background clustering has finished.
+    pub fn poll_topics(&mut self) {
+        let Some(result) = self.topics_build.as_ref().and_then(|b| b.poll()) else {
+            return;
+        };
+        self.topics_build = None;
+        match result {
+            Ok(topics) => {
+                self.topic_labels = topics
+                    .iter()
+                    .map(|t| t.label(3, |id| self.item(id).map(|it| get_display_title(&it.summary))))
+                    .collect();
+                self.status_msg = format!("{} topics", topics.len());
+                self.topics = topics;
+                self.topics_selected = 0;
+                self.topic_open = None;
             }
-            View::Similar => {
-                if let Some(group) = self.grouped_similar_results.get(self.similar_selected) {
-                    if let Some(item) = group.items.first() {
-                        let id = item.identifier;
-                        if let Some(row) = self.db.get_transcript(id).await? {
-                            self.detail = Some(row);
-                            self.detail_tab = DetailTab::Summary;
-                            self.detail_scroll = 0;
-                            self.view = View::Detail;
-                        }
-                    }
-                }
+            Err(e) => self.status_msg = format!("Topic clustering failed: {}", e),
+        }
+    }
+
+    /// Whether topics are still being computed.
+    pub fn topics_pending(&self) -> bool {
+        self.topics_build.is_some()
+    }
+
+    /// Drill into the selected topic's member list.
+    pub fn open_topic(&mut self) {
+        if self.topics_selected < self.topics.len() {
+            self.topic_open = Some(self.topics_selected);
+            self.topic_member_selected = 0;
+        }
+    }
+
+    /// Identifier of the selected member of the open topic.
+    pub fn selected_topic_member(&self) -> Option<i64> {
+        let topic = self.topics.get(self.topic_open?)?;
+        topic.members.get(self.topic_member_selected).copied()
+    }
+
+    pub fn topics_next(&mut self, step: usize) {
+        match self.topic_open.and_then(|t| self.topics.get(t)) {
+            Some(topic) => {
+                self.topic_member_selected =
+                    (self.topic_member_selected + step).min(topic.members.len().saturating_sub(1));
             }
-            _ => {}
+            None => self.topics_selected = (self.topics_selected + step).min(self.topics.len().saturating_sub(1)),
         }
-        Ok(())
+    }
+
+    pub fn topics_prev(&mut self, step: usize) {
+        match self.topic_open {
+            Some(_) => self.topic_member_selected = self.topic_member_selected.saturating_sub(step),
+            None => self.topics_selected = self.topics_selected.saturating_sub(step),
+        }
+    }
+
+    /// Show cost and token statistics of the filtered list, grouped by `stats_group_by`.
+    pub fn open_stats(&mut self) {
+        self.stats_groups = breakdown(self.filtered_indices.iter().map(|&i| &self.all_items[i]), self.stats_group_by);
+        self.stats_selected = 0;
+        self.view = View::Stats;
+    }
+
+    /// Group the stats view by the next dimension.
+    pub fn cycle_stats_group(&mut self) {
+        self.stats_group_by = self.stats_group_by.next();
+        self.open_stats();
+    }
+
+    pub fn stats_next(&mut self, step: usize) {
+        self.stats_selected = (self.stats_selected + step).min(self.stats_groups.len().saturating_sub(1));
+    }
+
+    pub fn stats_prev(&mut self, step: usize) {
+        self.stats_selected = self.stats_selected.saturating_sub(step);
     }
 
     /// Toggle expansion of the currently selected group.
@@ -505,13 +946,87 @@ impl App {
             _ => return Ok(()),
         };
 
+        if !self.db.schema().has_embeddings() {
+            self.status_msg = "Similarity search unavailable: database has no embedding column".to_string();
+            return Ok(());
+        }
+
         self.status_msg = "Computing similarities...".to_string();
         self.similar_source_id = id;
         self.similar_source_preview = preview;
         
-        self.similar_results = self.db.find_similar(id, 20).await?;
-        
-        // Grouping logic for similarity results
+        self.similar_seed_ids.clear();
+        let results = self.query_similar(0).await?;
+        self.show_similar_results(results);
+        Ok(())
+    }
+
+    /// Toggle the mark on the selected list entry. Marked entries seed `open_centroid_similar`.
+    pub fn toggle_mark(&mut self) {
+        let Some(item) = self.list_items.get(self.list_selected).and_then(|g| g.items.first()) else {
+            return;
+        };
+        let id = item.identifier;
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.status_msg = format!("{} marked (S: similar to all marked)", self.marked.len());
+    }
+
+    /// Open the similar view for the centroid of the marked transcripts.
+    ///
+    /// Without marks, the bookmarked entries of the current (filtered) list are
+    /// used, so a tag filter plus bookmarks selects a whole topic collection.
+    pub async fn open_centroid_similar(&mut self) -> turso::Result<()> {
+        if !self.db.schema().has_embeddings() {
+            self.status_msg = "Similarity search unavailable: database has no embedding column".to_string();
+            return Ok(());
+        }
+
+        let seeds: Vec<i64> = if self.marked.is_empty() {
+            self.filtered_indices
+                .iter()
+                .map(|&i| &self.all_items[i])
+                .filter(|item| item.bookmarked)
+                .map(|item| item.identifier)
+                .collect()
+        } else {
+            self.marked.iter().copied().collect()
+        };
+        if seeds.is_empty() {
+            self.status_msg = "Mark entries with m (or bookmark them) to search by centroid".to_string();
+            return Ok(());
+        }
+
+        self.status_msg = "Computing similarities...".to_string();
+        let mut query = self.settings.similarity.query(0);
+        query.candidates = self.ann_candidates(&seeds, self.settings.similarity.page_size);
+        let (results, used) = self.db.find_similar_to_centroid(&seeds, &query).await?;
+        if used == 0 {
+            self.status_msg = "None of the selected entries has an embedding".to_string();
+            return Ok(());
+        }
+
+        let ids: Vec<String> = seeds.iter().map(|id| id.to_string()).collect();
+        self.similar_source_id = seeds[0];
+        self.similar_source_preview = format!("Centroid of {} items: {}", used, ids.join(", "));
+        self.similar_seed_ids = seeds;
+        self.show_similar_results(results);
+        Ok(())
+    }
+
+    /// Show a fresh first page of results in the similar view.
+    fn show_similar_results(&mut self, results: Vec<SimilarResult>) {
+        self.similar_exhausted = results.len() < self.settings.similarity.page_size;
+        self.similar_results = results;
+        self.group_similar_results();
+        self.similar_selected = 0;
+        self.view = View::Similar;
+        self.status_msg = format!("Found {} similar transcripts ({} groups)", self.similar_results.len(), self.grouped_similar_results.len());
+    }
+
+    /// Group consecutive identical results.
+    fn group_similar_results(&mut self) {
         self.grouped_similar_results.clear();
         if !self.similar_results.is_empty() {
             let mut current_group: Vec<SimilarResult> = Vec::new();
@@ -537,85 +1052,353 @@ impl App {
                 });
             }
         }
-        
-        self.similar_selected = 0;
-        self.view = View::Similar;
-        self.status_msg = format!("Found {} similar transcripts ({} groups)", self.similar_results.len(), self.grouped_similar_results.len());
+    }
+
+    /// Re-run the current similarity search (single source or centroid) from `offset`.
+    async fn query_similar(&self, offset: usize) -> turso::Result<Vec<SimilarResult>> {
+        let mut query = self.settings.similarity.query(offset);
+        let seeds = if self.similar_seed_ids.is_empty() {
+            vec![self.similar_source_id]
+        } else {
+            self.similar_seed_ids.clone()
+        };
+        query.candidates = self.ann_candidates(&seeds, offset + self.settings.similarity.page_size);
+        if self.similar_seed_ids.is_empty() {
+            self.db.find_similar(self.similar_source_id, &query).await
+        } else {
+            Ok(self.db.find_similar_to_centroid(&self.similar_seed_ids, &query).await?.0)
+        }
+    }
+
+    /// The ANN index, if it is enabled and matches the configured search: it
+    /// ranks by cosine distance over a fixed prefix length, so other metrics
+    /// and slice lengths scan the whole table.
+    fn usable_ann(&self) -> Option<&HnswIndex> {
+        let similarity = &self.settings.similarity;
+        if !similarity.use_index || similarity.metric != Metric::Cosine {
+            return None;
+        }
+        self.ann.as_ref().filter(|index| similarity.dims == Some(index.dims()))
+    }
+
+    /// Candidates for the first `k` results near `seeds` from the ANN index,
+    /// or `None` to scan the whole table.
+    fn ann_candidates(&self, seeds: &[i64], k: usize) -> Option<Vec<i64>> {
+        let index = self.usable_ann()?;
+        // Seeds missing from the index (e.g. shorter embeddings) fall back to a scan.
+        let vectors: Vec<Vec<f32>> = seeds
+            .iter()
+            .map(|id| index.vector_of(*id).map(<[f32]>::to_vec))
+            .collect::<Option<_>>()?;
+        let centroid = crate::vector::centroid(&vectors)?;
+        let pool = if self.settings.similarity.exact_rerank { k * RERANK_POOL } else { k };
+        Some(
+            index
+                .search(&centroid, pool + seeds.len())
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect(),
+        )
+    }
+
+    /// Pick up the ANN indexes once their background builds have finished,
+    /// updating the duplicates view if it was waiting for one.
+    pub fn poll_ann_index(&mut self) {
+        let waiting = self.duplicates_index().is_none();
+        if let Some(result) = self.ann_build.as_ref().and_then(|b| b.poll()) {
+            self.ann_build = None;
+            match result {
+                Ok(index) => {
+                    self.status_msg = format!("Similarity index ready ({} embeddings)", index.len());
+                    self.ann = Some(index);
+                }
+                Err(e) => self.status_msg = format!("Similarity index failed: {}", e),
+            }
+        }
+        if let Some(result) = self.duplicates_build.as_ref().and_then(|b| b.poll()) {
+            self.duplicates_build = None;
+            match result {
+                Ok(index) => self.duplicates_ann = Some(index),
+                Err(e) => self.status_msg = format!("Similarity index failed: {}", e),
+            }
+        }
+        if waiting && self.duplicates_index().is_some() && self.view == View::Duplicates {
+            self.refresh_duplicates();
+        }
+    }
+
+    /// Short state of the ANN index for the Similar view header.
+    pub fn ann_status(&self) -> &'static str {
+        match (&self.ann, &self.ann_build) {
+            _ if !self.settings.similarity.use_index => "off",
+            (Some(_), _) if self.usable_ann().is_none() => "exact",
+            (Some(_), _) => "on",
+            (None, Some(_)) => "building",
+            (None, None) => "unavailable",
+        }
+    }
+
+    /// Toggle use of the ANN index and re-run the search.
+    pub async fn toggle_similarity_index(&mut self) -> turso::Result<()> {
+        self.settings.similarity.use_index = !self.settings.similarity.use_index;
+        self.refresh_similar().await
+    }
+
+    /// Toggle exact re-ranking of a larger ANN candidate pool and re-run the search.
+    pub async fn toggle_similarity_rerank(&mut self) -> turso::Result<()> {
+        self.settings.similarity.exact_rerank = !self.settings.similarity.exact_rerank;
+        self.refresh_similar().await
+    }
+
+    /// Append the next page of results to the similar view.
+    pub async fn load_more_similar(&mut self) -> turso::Result<()> {
+        if self.similar_exhausted {
+            self.status_msg = "No more results".to_string();
+            return Ok(());
+        }
+        let more = self.query_similar(self.similar_results.len()).await?;
+        self.similar_exhausted = more.len() < self.settings.similarity.page_size;
+        self.similar_results.extend(more);
+        self.group_similar_results();
+        self.similar_selected = self.similar_selected.min(self.grouped_similar_results.len().saturating_sub(1));
+        self.status_msg = format!("{} similar transcripts loaded", self.similar_results.len());
+        Ok(())
+    }
+
+    /// Cycle the similarity metric and re-run the search.
+    pub async fn cycle_similarity_metric(&mut self) -> turso::Result<()> {
+        self.settings.similarity.cycle_metric();
+        self.refresh_similar().await
+    }
+
+    /// Cycle the embedding slice length and re-run the search.
+    pub async fn cycle_similarity_dims(&mut self) -> turso::Result<()> {
+        self.settings.similarity.cycle_dims();
+        self.refresh_similar().await
+    }
+
+    /// Cut results off at the distance of the selected one, or clear the cut-off.
+    pub async fn toggle_similarity_cutoff(&mut self) -> turso::Result<()> {
+        self.settings.similarity.max_distance = match self.settings.similarity.max_distance {
+            Some(_) => None,
+            None => match self.grouped_similar_results.get(self.similar_selected) {
+                Some(group) => group.items.first().map(|r| r.distance),
+                None => return Ok(()),
+            },
+        };
+        self.refresh_similar().await
+    }
+
+    /// Persist the settings and reload the first page with them.
+    async fn refresh_similar(&mut self) -> turso::Result<()> {
+        let results = self.query_similar(0).await?;
+        self.show_similar_results(results);
+        let s = &self.settings.similarity;
+        self.status_msg = format!(
+            "{} results · {} · {} dims{}",
+            self.similar_results.len(),
+            s.metric.label(),
+            s.dims_label(),
+            s.max_distance.map(|d| format!(" · max {:.3}", d)).unwrap_or_default()
+        );
+        if let Err(e) = self.save_settings() {
+            self.status_msg = format!("{} ({})", self.status_msg, e);
+        }
         Ok(())
     }
 
+    /// Use `settings`, persisting changes to `path` if given.
+    pub fn set_settings(&mut self, settings: Settings, path: Option<PathBuf>) {
+        self.settings = settings;
+        self.settings_path = path;
+    }
+
+    fn save_settings(&self) -> Result<(), SettingsError> {
+        match self.settings_path {
+            Some(ref path) => self.settings.save(path),
+            None => Ok(()),
+        }
+    }
+
     /// Apply filter and reset list.
     pub fn apply_filter(&mut self) {
         let filter = self.filter.to_lowercase();
         self.filtered_indices.clear();
+
+        for (i, item) in self.all_items.iter().enumerate() {
+            if query::matches_text(item, &filter)
+                && self.global_filter.as_ref().is_none_or(|gf| gf.matches(item))
+            {
+                self.filtered_indices.push(i);
+            }
+        }
         
-        if filter.is_empty() {
-            for (i, item) in self.all_items.iter().enumerate() {
-                if let Some(ref gf) = self.global_filter {
-                    if gf.matches(item) {
-                        self.filtered_indices.push(i);
-                    }
-                } else {
-                    self.filtered_indices.push(i);
-                }
+        self.rebuild_groups();
+
+        self.status_msg = format!(
+            "{} groups found for \"{}\"",
+            self.grouped_items.len(),
+            if self.filter.is_empty() { "all" } else { &self.filter }
+        );
+
+        self.restart_transcript_search();
+    }
+
+    /// Toggle whether the filter text is also searched in transcript bodies.
+    pub fn toggle_transcript_search(&mut self) {
+        self.search_transcripts = !self.search_transcripts;
+        self.apply_filter();
+        if !self.search_transcripts {
+            self.status_msg = "Transcript search off".to_string();
+        }
+    }
+
+    /// Cancel any running transcript scan and start a new one for the current filter.
+    fn restart_transcript_search(&mut self) {
+        // Dropping the old handle aborts its task.
+        self.transcript_search = None;
+        if !self.search_transcripts || self.filter.trim().is_empty() {
+            return;
+        }
+        match TranscriptSearch::spawn(&self.db, &self.filter, self.all_items.len()) {
+            Ok(search) => self.transcript_search = Some(search),
+            Err(e) => self.status_msg = format!("Transcript search failed: {}", e),
+        }
+    }
+
+    /// Merge identifiers streamed from the transcript scan into the current list.
+    pub fn poll_transcript_search(&mut self) {
+        let Some(search) = self.transcript_search.as_mut() else {
+            return;
+        };
+        let new_ids = search.poll();
+        if let Some(ref e) = search.error {
+            self.status_msg = format!("Transcript search failed: {}", e);
+        }
+        if new_ids.is_empty() {
+            return;
+        }
+
+        let present: HashSet<usize> = self.filtered_indices.iter().copied().collect();
+        let mut added = false;
+        for id in new_ids {
+            let Some(&idx) = self.positions.get(&id) else {
+                continue;
+            };
+            let passes = match self.global_filter {
+                Some(ref gf) => gf.matches(&self.all_items[idx]),
+                None => true,
+            };
+            if passes && !present.contains(&idx) {
+                self.filtered_indices.push(idx);
+                added = true;
             }
-        } else {
-            for (i, item) in self.all_items.iter().enumerate() {
-                let matches_text = item.summary.to_lowercase().contains(&filter) 
-                   || item.host.to_lowercase().contains(&filter)
-                   || item.original_source_link.to_lowercase().contains(&filter);
-                
-                if matches_text {
-                    if let Some(ref gf) = self.global_filter {
-                        if gf.matches(item) {
-                            self.filtered_indices.push(i);
-                        }
-                    } else {
-                        self.filtered_indices.push(i);
-                    }
+        }
+
+        if added {
+            // Keep the user's position while results stream in.
+            self.regroup_keep_position();
+        }
+    }
+
+    /// Rebuild groups from `filtered_indices` without moving the selection.
+    fn regroup_keep_position(&mut self) {
+        self.group_filtered();
+        if self.list_offset >= self.grouped_items.len() {
+            self.list_offset = 0;
+            self.list_selected = 0;
+        }
+        self.update_list_page();
+        self.list_selected = self.list_selected.min(self.list_items.len().saturating_sub(1));
+    }
+
+    /// Rank all transcripts against the filter text using the full-text index,
+    /// which also covers transcript bodies and timestamps. The index is built
+    /// in the background on first use and the list is ranked once it is ready.
+    pub fn apply_fulltext_search(&mut self) -> turso::Result<()> {
+        if self.filter.trim().is_empty() {
+            self.status_msg = "Type a query with / first, then press F for full-text search".to_string();
+            return Ok(());
+        }
+        let Some(ref index) = self.fulltext else {
+            if self.fulltext_build.is_none() {
+                self.fulltext_build = Some(FulltextBuild::spawn(&self.db, self.all_items.len())?);
+            }
+            self.fulltext_pending = true;
+            self.status_msg = "Building full-text index...".to_string();
+            return Ok(());
+        };
+
+        let hits = index.search(&self.filter, self.all_items.len());
+
+        self.transcript_search = None;
+        self.filtered_indices.clear();
+        for hit in &hits {
+            if let Some(&idx) = self.positions.get(&hit.identifier) {
+                let passes = match self.global_filter {
+                    Some(ref gf) => gf.matches(&self.all_items[idx]),
+                    None => true,
+                };
+                if passes {
+                    self.filtered_indices.push(idx);
                 }
             }
         }
-        
-        // Grouping logic
-        self.grouped_items.clear();
-        if !self.filtered_indices.is_empty() {
-            let mut current_group: Vec<TranscriptListItem> = Vec::new();
-            
-            for &idx in &self.filtered_indices {
-                let item = &self.all_items[idx];
-                if let Some(last) = current_group.last() {
-                    // Group if summary is near-identical (just heuristic)
-                    if last.summary == item.summary {
-                        current_group.push(item.clone());
-                    } else {
-                        self.grouped_items.push(TranscriptGroup {
-                            items: current_group,
-                            expanded: false,
-                        });
-                        current_group = vec![item.clone()];
-                    }
-                } else {
-                    current_group.push(item.clone());
+
+        self.rebuild_groups();
+        self.status_msg = match hits.first() {
+            Some(top) => format!(
+                "{} full-text matches for \"{}\" (top: {} in {})",
+                self.filtered_indices.len(),
+                self.filter,
+                top.identifier,
+                top.best_field.label()
+            ),
+            None => format!("No full-text matches for \"{}\"", self.filter),
+        };
+        Ok(())
+    }
+
+    /// Pick up the full-text index once the background build has finished,
+    /// and run the search that was waiting for it.
+    pub fn poll_fulltext_index(&mut self) -> turso::Result<()> {
+        let Some(result) = self.fulltext_build.as_mut().and_then(|b| b.poll()) else {
+            return Ok(());
+        };
+        self.fulltext_build = None;
+        match result {
+            Ok(index) => {
+                self.fulltext = Some(index);
+                if std::mem::take(&mut self.fulltext_pending) {
+                    return self.apply_fulltext_search();
                 }
+                self.status_msg = "Full-text index ready".to_string();
             }
-            if !current_group.is_empty() {
-                self.grouped_items.push(TranscriptGroup {
-                    items: current_group,
-                    expanded: false,
-                });
+            Err(e) => {
+                self.fulltext_pending = false;
+                self.status_msg = format!("Full-text index failed: {}", e);
             }
         }
+        Ok(())
+    }
 
+    /// Group consecutive rows of `filtered_indices` and reset paging.
+    fn rebuild_groups(&mut self) {
+        self.group_filtered();
         self.list_offset = 0;
         self.list_selected = 0;
         self.update_list_page();
-        
-        self.status_msg = format!(
-            "{} groups found for \"{}\"",
-            self.grouped_items.len(),
-            if self.filter.is_empty() { "all" } else { &self.filter }
-        );
+    }
+
+    fn group_filtered(&mut self) {
+        let groups = query::group_consecutive(self.filtered_indices.iter().map(|&idx| &self.all_items[idx]));
+        self.grouped_items = groups
+            .into_iter()
+            .map(|group| TranscriptGroup {
+                items: group.into_iter().cloned().collect(),
+                expanded: false,
+            })
+            .collect();
     }
 
     pub fn scroll_down(&mut self) {
@@ -0,0 +1,232 @@
+//! Preparing a database for sharing: drop rows whose summary is a processing
+//! error, empty the large text columns and truncate embeddings (the native
+//! version of `tools/cleanup_db.py`), then copy the result into a fresh file.
+
+use crate::db::Database;
+use crate::import::checkpoint;
+use clap::ValueEnum;
+use std::path::Path;
+use turso::Value;
+
+/// Embedding columns outside the schema mapping that are truncated as well.
+const EXTRA_EMBEDDING_COLUMNS: [&str; 1] = ["full_embedding"];
+
+/// Text columns `cleanup` can empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StripColumn {
+    Transcript,
+    Timestamps,
+    /// The YouTube-formatted timestamped summary
+    YoutubeTimestamps,
+}
+
+impl StripColumn {
+    /// Logical column name.
+    pub fn column(self) -> &'static str {
+        match self {
+            Self::Transcript => "transcript",
+            Self::Timestamps => "timestamps",
+            Self::YoutubeTimestamps => "timestamped_summary_in_youtube_format",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanupOptions {
+    pub strip: Vec<StripColumn>,
+    /// Embeddings longer than this many float32 values are cut; `None` keeps them.
+    pub embedding_dims: Option<usize>,
+}
+
+/// What a cleanup changes. Stripped and truncated counts exclude deleted error rows.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupReport {
+    pub rows: usize,
+    pub error_rows: usize,
+    /// Non-empty values emptied, per column.
+    pub stripped: Vec<(String, usize)>,
+    /// Embeddings truncated, per column.
+    pub truncated: Vec<(String, usize)>,
+}
+
+async fn count(db: &Database, condition: &str) -> turso::Result<usize> {
+    let mut rows = db
+        .connection()
+        .query(format!("SELECT COUNT(*) FROM {} WHERE {}", db.schema().table_sql(), condition), ())
+        .await?;
+    Ok(match rows.next().await? {
+        Some(row) => match row.get_value(0)? {
+            Value::Integer(n) => n as usize,
+            _ => 0,
+        },
+        None => 0,
+    })
+}
+
+/// Quoted embedding columns present in the table: the mapped `embedding` plus `EXTRA_EMBEDDING_COLUMNS`.
+async fn embedding_columns(db: &Database) -> turso::Result<Vec<String>> {
+    let schema = db.schema();
+    let mut columns = Vec::new();
+    if schema.has_embeddings() {
+        columns.push(schema.col("embedding"));
+    }
+    let mut rows = db
+        .connection()
+        .query(format!("PRAGMA table_info({})", schema.table_sql()), ())
+        .await?;
+    while let Some(row) = rows.next().await? {
+        if let Value::Text(name) = row.get_value(1)?
+            && EXTRA_EMBEDDING_COLUMNS.iter().any(|c| c.eq_ignore_ascii_case(&name))
+        {
+            columns.push(format!("\"{}\"", name));
+        }
+    }
+    Ok(columns)
+}
+
+/// Count what the cleanup changes in `db` and, unless `dry_run`, apply it.
+pub async fn cleanup(db: &Database, options: &CleanupOptions, dry_run: bool) -> turso::Result<CleanupReport> {
+    let schema = db.schema();
+    let table = schema.table_sql();
+    let summary = schema.col("summary");
+    // Summaries like "Error: resource exhausted"
+    let is_error = format!(
+        "(COALESCE({s}, '') LIKE 'Error:%' OR COALESCE({s}, '') LIKE '% Error: %')",
+        s = summary
+    );
+    let kept = format!("NOT {}", is_error);
+
+    let mut report = CleanupReport {
+        rows: count(db, "1").await?,
+        error_rows: count(db, &is_error).await?,
+        ..Default::default()
+    };
+    let mut statements = vec![format!("DELETE FROM {} WHERE {}", table, is_error)];
+
+    for strip in &options.strip {
+        if !schema.has(strip.column()) {
+            continue;
+        }
+        let col = schema.col(strip.column());
+        let non_empty = format!("COALESCE({}, '') != ''", col);
+        report.stripped.push((strip.column().to_string(), count(db, &format!("{} AND {}", kept, non_empty)).await?));
+        statements.push(format!("UPDATE {} SET {} = '' WHERE {}", table, col, non_empty));
+    }
+
+    if let Some(dims) = options.embedding_dims {
+        let bytes = dims * 4;
+        for col in embedding_columns(db).await? {
+            let too_long = format!("length({}) > {}", col, bytes);
+            report.truncated.push((col.trim_matches('"').to_string(), count(db, &format!("{} AND {}", kept, too_long)).await?));
+            statements.push(format!("UPDATE {} SET {c} = substr({c}, 1, {}) WHERE {}", table, bytes, too_long, c = col));
+        }
+    }
+
+    if dry_run {
+        return Ok(report);
+    }
+    let conn = db.connection();
+    conn.execute("BEGIN", ()).await?;
+    for sql in &statements {
+        conn.execute(sql, ()).await?;
+    }
+    conn.execute("COMMIT", ()).await?;
+    Ok(report)
+}
+
+/// Copy every table and index of `db` into a new database at `path`.
+///
+/// turso has no `VACUUM`, and the free pages left by a cleanup still hold the
+/// deleted text, so the result is rebuilt rather than shrunk in place.
+pub async fn compact(db: &Database, path: &Path) -> turso::Result<()> {
+    let src = db.connection();
+    let mut objects: Vec<(String, String, String)> = Vec::new();
+    let mut rows = src
+        .query(
+            "SELECT type, name, sql FROM sqlite_master \
+             WHERE sql IS NOT NULL AND type IN ('table', 'index') AND name NOT LIKE 'sqlite_%' \
+             ORDER BY type DESC, name",
+            (),
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        objects.push((row.get(0)?, row.get(1)?, row.get(2)?));
+    }
+    drop(rows);
+
+    let dest = turso::Builder::new_local(&path.to_string_lossy()).build().await?.connect()?;
+    dest.execute("BEGIN", ()).await?;
+    // Tables sort before indexes, so rows are copied before indexes are built
+    for (kind, name, sql) in &objects {
+        dest.execute(sql, ()).await?;
+        if kind != "table" {
+            continue;
+        }
+        let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+        let mut rows = src.query(format!("SELECT * FROM {}", quoted), ()).await?;
+        let mut insert = None;
+        while let Some(row) = rows.next().await? {
+            let values: Vec<Value> = (0..row.column_count()).map(|i| row.get_value(i)).collect::<turso::Result<_>>()?;
+            let sql = insert.get_or_insert_with(|| {
+                let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+                format!("INSERT INTO {} VALUES ({})", quoted, placeholders.join(", "))
+            });
+            dest.execute(sql.as_str(), turso::params::Params::Positional(values)).await?;
+        }
+    }
+    dest.execute("COMMIT", ()).await?;
+    checkpoint(&dest).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cleanup_strips_and_truncates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("full.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy())
+            .build()
+            .await
+            .unwrap()
+            .connect()
+            .unwrap();
+        conn.execute(
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, transcript TEXT, \
+             timestamps TEXT, embedding BLOB, full_embedding BLOB)",
+            (),
+        )
+        .await
+        .unwrap();
+        for sql in [
+            "INSERT INTO items VALUES (1, 'fine', 'words', '00:01 x', zeroblob(16), zeroblob(8))",
+            "INSERT INTO items VALUES (2, 'Error: resource exhausted', 'words', '', zeroblob(16), NULL)",
+            "INSERT INTO items VALUES (3, 'also fine', '', 'ts', zeroblob(4), NULL)",
+        ] {
+            conn.execute(sql, ()).await.unwrap();
+        }
+        drop(conn);
+
+        let db = Database::open(&path).await.unwrap();
+        let options = CleanupOptions { strip: vec![StripColumn::Transcript], embedding_dims: Some(2) };
+        let planned = cleanup(&db, &options, true).await.unwrap();
+        assert_eq!(planned.error_rows, 1);
+        assert_eq!(planned.stripped, vec![("transcript".to_string(), 1)]);
+        assert_eq!(
+            planned.truncated,
+            vec![("embedding".to_string(), 1), ("full_embedding".to_string(), 0)]
+        );
+        assert_eq!(db.list_all_transcripts().await.unwrap().len(), 3);
+
+        assert_eq!(cleanup(&db, &options, false).await.unwrap(), planned);
+        let compacted = dir.path().join("clean.db");
+        compact(&db, &compacted).await.unwrap();
+        let db = Database::open(&compacted).await.unwrap();
+        let row = db.get_transcript(1).await.unwrap().unwrap();
+        assert_eq!((row.transcript.as_str(), row.timestamps.as_str()), ("", "00:01 x"));
+        assert!(db.get_transcript(2).await.unwrap().is_none());
+        let embeddings = db.get_embeddings(&[1, 3], 8).await.unwrap();
+        assert!(embeddings.iter().all(|(_, v)| v.len() <= 2), "{:?}", embeddings);
+    }
+}
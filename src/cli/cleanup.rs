@@ -0,0 +1,113 @@
+use crate::cleanup::{cleanup, compact, CleanupOptions, CleanupReport};
+use crate::codec;
+use crate::db::Database;
+use super::{Keys, RecipientArgs};
+use std::path::{Path, PathBuf};
+
+/// Options of the `cleanup` subcommand.
+pub struct CleanupArgs {
+    pub input: PathBuf,
+    /// Required unless `dry_run`.
+    pub output: Option<PathBuf>,
+    pub options: CleanupOptions,
+    /// Only report what would change.
+    pub dry_run: bool,
+    /// Encrypt the output to these recipients, or with the password if there are none.
+    pub encrypt: Option<RecipientArgs>,
+    /// Decrypt an encrypted input into memory instead of a temporary file.
+    pub in_memory: bool,
+}
+
+fn print_report(report: &CleanupReport, dry_run: bool) {
+    let verb = if dry_run { "would be" } else { "were" };
+    eprintln!("{} rows, {} error rows {} removed", report.rows, report.error_rows, verb);
+    for (column, n) in &report.stripped {
+        eprintln!("  {}: {} values {} emptied", column, n, verb);
+    }
+    for (column, n) in &report.truncated {
+        eprintln!("  {}: {} embeddings {} truncated", column, n, verb);
+    }
+}
+
+/// Copy `input` (decoding it if needed) to `work`, including a write-ahead log next to a plain file.
+fn copy_input(input: &Path, work: &Path, keys: &Keys<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    let kind = codec::detect(input)?;
+    if kind.is_encoded() {
+        codec::decode_stream(input, work, keys.credentials(&kind)?.as_ref(), &mut super::phase_timer())
+            .map_err(|e| format!("Failed to decode database: {}", e))?;
+    } else {
+        std::fs::copy(input, work)?;
+        let wal = PathBuf::from(format!("{}-wal", input.display()));
+        if wal.exists() {
+            std::fs::copy(&wal, format!("{}-wal", work.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a cleaned copy of the input database, or with `dry_run` just report what would change.
+pub async fn run(args: &CleanupArgs, keys: &Keys<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.dry_run {
+        let opened = super::open_database(&args.input, keys, args.in_memory).await?;
+        print_report(&cleanup(&opened.db, &args.options, true).await?, true);
+        return Ok(());
+    }
+
+    if args.in_memory && args.encrypt.is_some() {
+        return Err(super::IN_MEMORY_ENCRYPT_ERROR.into());
+    }
+    let output = args.output.as_deref().ok_or("--output is required unless --dry-run is given")?;
+    if !args.input.exists() {
+        return Err(format!("database file not found: {}", args.input.display()).into());
+    }
+    if output.exists() {
+        return Err(format!("output file already exists: {}", output.display()).into());
+    }
+
+    // Clean a copy in a temporary directory (or in memory), then rebuild it
+    // into the output (or, without `in_memory`, into a second temporary file
+    // that gets encrypted)
+    let dir = tempfile::tempdir()?;
+    let db = if args.in_memory && codec::detect(&args.input)?.is_encoded() {
+        super::open_database(&args.input, keys, true).await?.db
+    } else {
+        let work = dir.path().join("work.db");
+        copy_input(&args.input, &work, keys)?;
+        Database::open(&work)
+            .await
+            .map_err(|e| format!("Failed to open database: {}", e))?
+    };
+    let report = cleanup(&db, &args.options, false).await?;
+    print_report(&report, false);
+
+    let cleaned = if args.encrypt.is_some() { dir.path().join("cleaned.db") } else { output.to_path_buf() };
+    compact(&db, &cleaned).await?;
+    drop(db);
+    if let Some(recipients) = &args.encrypt {
+        let encryption = recipients.encryption(keys.password, &format!("Enter password for {}: ", output.display()))?;
+        codec::encrypt_stream(&cleaned, output, &encryption, &codec::Compression::new(super::ENCRYPT_QUALITY))?;
+    }
+    let size = std::fs::metadata(output)?.len() as f64 / (1024.0 * 1024.0);
+    eprintln!("Wrote {} ({:.2} MB)", output.display(), size);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cleanup_refuses_encrypt_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = CleanupArgs {
+            input: dir.path().join("input.db.age"),
+            output: Some(dir.path().join("cleaned.db.age")),
+            options: CleanupOptions { strip: Vec::new(), embedding_dims: None },
+            dry_run: false,
+            encrypt: Some(RecipientArgs::default()),
+            in_memory: true,
+        };
+        let err = run(&args, &Keys::default()).await.unwrap_err();
+        assert_eq!(err.to_string(), crate::cli::IN_MEMORY_ENCRYPT_ERROR);
+    }
+}
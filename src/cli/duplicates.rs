@@ -0,0 +1,89 @@
+use crate::ann;
+use crate::app::get_display_title;
+use crate::db::{Database, TranscriptListItem, SIMILARITY_DIMS};
+use crate::duplicates::{find_duplicates, DuplicateCluster};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Options of the `duplicates` subcommand.
+pub struct DuplicatesArgs {
+    pub max_distance: f32,
+    pub links_only: bool,
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct JsonMember<'a> {
+    identifier: i64,
+    link: &'a str,
+    title: String,
+}
+
+#[derive(Serialize)]
+struct JsonCluster<'a> {
+    #[serde(flatten)]
+    cluster: &'a DuplicateCluster,
+    items: Vec<JsonMember<'a>>,
+}
+
+/// Print the duplicate clusters of `db` to stdout.
+pub async fn run(db: &Database, args: &DuplicatesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let items = db.list_all_transcripts().await?;
+    let index = if args.links_only || !db.schema().has_embeddings() {
+        None
+    } else {
+        eprintln!("Loading similarity index...");
+        Some(ann::load_or_build(db.reader()?, SIMILARITY_DIMS).await?)
+    };
+    let clusters = find_duplicates(&items, index.as_ref(), args.max_distance);
+    let by_id: HashMap<i64, &TranscriptListItem> = items.iter().map(|i| (i.identifier, i)).collect();
+
+    if args.json {
+        let report: Vec<JsonCluster> = clusters
+            .iter()
+            .map(|cluster| JsonCluster {
+                cluster,
+                items: cluster
+                    .members
+                    .iter()
+                    .filter_map(|id| by_id.get(id))
+                    .map(|item| JsonMember {
+                        identifier: item.identifier,
+                        link: &item.original_source_link,
+                        title: get_display_title(&item.summary),
+                    })
+                    .collect(),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let duplicates: usize = clusters.iter().map(|c| c.members.len() - 1).sum();
+    println!(
+        "{} duplicate clusters, {} redundant entries out of {}",
+        clusters.len(),
+        duplicates,
+        items.len()
+    );
+    for (n, cluster) in clusters.iter().enumerate() {
+        let reasons: Vec<&str> = cluster.reasons.iter().map(|r| r.label()).collect();
+        let distance = cluster
+            .min_distance
+            .map(|d| format!(", min distance {:.3}", d))
+            .unwrap_or_default();
+        println!();
+        println!("Cluster {} ({} items; {}{})", n + 1, cluster.members.len(), reasons.join(", "), distance);
+        for id in &cluster.members {
+            if let Some(item) = by_id.get(id) {
+                println!(
+                    "  {:>6}  {}  {}",
+                    item.identifier,
+                    item.original_source_link,
+                    get_display_title(&item.summary)
+                );
+            }
+        }
+    }
+    Ok(())
+}
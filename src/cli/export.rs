@@ -0,0 +1,43 @@
+use crate::db::Database;
+use crate::export::{write_export, Column, ExportFormat, DEFAULT_COLUMNS};
+use crate::query;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Options of the `export` subcommand.
+pub struct ExportArgs {
+    /// Free-text filter, as typed after `/` in the TUI.
+    pub text: String,
+    /// Filter expressions, combined with AND.
+    pub filters: Vec<String>,
+    pub columns: Vec<Column>,
+    /// Inferred from the output file extension if not given, else JSON.
+    pub format: Option<ExportFormat>,
+    /// Standard output if not given.
+    pub output: Option<PathBuf>,
+}
+
+/// Write the rows of `db` matching the filters.
+pub async fn run(db: &Database, args: &ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = query::parse_filters(&args.filters)?;
+    let items = super::load_items(db).await?;
+    let selected = query::select(&items, &args.text, filter.as_ref());
+    let columns: &[Column] = if args.columns.is_empty() { &DEFAULT_COLUMNS } else { &args.columns };
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Json);
+
+    match &args.output {
+        Some(path) => {
+            let mut out = BufWriter::new(std::fs::File::create(path)?);
+            write_export(db, &selected, columns, format, &mut out).await?;
+            eprintln!("Exported {} of {} entries to {}", selected.len(), items.len(), path.display());
+        }
+        None => {
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            write_export(db, &selected, columns, format, &mut out).await?;
+        }
+    }
+    Ok(())
+}
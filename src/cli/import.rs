@@ -0,0 +1,46 @@
+use crate::codec;
+use crate::import::{collect_files, read_transcript, Importer};
+use std::path::{Path, PathBuf};
+
+/// Import transcript files into the plain database at `db_path`, creating it if needed.
+///
+/// Files that cannot be read are reported on stderr and skipped.
+pub async fn run(db_path: &Path, paths: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let is_age_name = db_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("age"));
+    if is_age_name || (db_path.exists() && codec::detect(db_path)?.is_encoded()) {
+        return Err(format!(
+            "cannot import into {}: import writes a plain database, encrypt it afterwards with `encrypt`",
+            db_path.display()
+        )
+        .into());
+    }
+    let files = collect_files(paths)?;
+    if files.is_empty() {
+        return Err("no .srt, .vtt, .json3 or .txt files found".into());
+    }
+
+    let mut importer = Importer::open(db_path).await?;
+    let (mut imported, mut present, mut failed) = (0, 0, 0);
+    for file in &files {
+        match read_transcript(file) {
+            Ok(transcript) => match importer.insert(&transcript).await? {
+                Some(_) => imported += 1,
+                None => present += 1,
+            },
+            Err(e) => {
+                eprintln!("Skipping: {}", e);
+                failed += 1;
+            }
+        }
+    }
+    importer.finish().await?;
+
+    eprintln!(
+        "Imported {} transcripts into {} ({} already present, {} failed)",
+        imported,
+        db_path.display(),
+        present,
+        failed
+    );
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::codec::{self, FileKind};
+use crate::schema::Schema;
+use crate::sqlite_file;
+use super::Keys;
+use std::path::Path;
+
+fn megabytes(bytes: usize) -> String {
+    format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn describe(kind: &FileKind) -> String {
+    match kind {
+        FileKind::Sqlite => "plain SQLite database".to_string(),
+        FileKind::AgePassphrase { work_factor } => {
+            format!("age, passphrase (scrypt work factor {}, N = 2^{})", work_factor, work_factor)
+        }
+        FileKind::AgeRecipients { stanzas } => format!("age, recipients ({})", stanzas.join(", ")),
+        FileKind::Compressed(codec) => format!("{}-compressed SQLite database (not encrypted)", codec.label()),
+        FileKind::Unknown => "unknown (neither SQLite, age, Brotli, zstd nor gzip)".to_string(),
+    }
+}
+
+/// Check a database image held in memory and print its tables with row counts.
+fn verify_image(image: &[u8]) -> Result<(), String> {
+    let header = sqlite_file::parse_header(image)?;
+    println!(
+        "SQLite:  page size {}, {} pages in header, {} in image",
+        header.page_size,
+        header.page_count,
+        image.len() / header.page_size
+    );
+    if !image.len().is_multiple_of(header.page_size) {
+        return Err(format!("image size {} is not a multiple of the page size", image.len()));
+    }
+
+    let tables = sqlite_file::tables(image)?;
+    let mut counts = Vec::with_capacity(tables.len());
+    for table in &tables {
+        let rows = sqlite_file::row_count(image, table.root_page)?;
+        println!("  {:<24} {:>10} rows", table.name, rows);
+        counts.push((table.name.clone(), rows));
+    }
+    let names: Vec<String> = tables.into_iter().map(|t| t.name).collect();
+    let table = Schema::find_table(&names).map_err(|e| e.to_string())?;
+    let rows = counts.iter().find(|(name, _)| *name == table).map_or(0, |(_, n)| *n);
+    println!("OK: valid SQLite database, {} rows in '{}'", rows, table);
+    Ok(())
+}
+
+/// Report the container of a database file and, where possible, check that it
+/// decodes into a valid database. Nothing decrypted is written to disk.
+///
+/// Encrypted files are only checked with `verify`, or when a password or
+/// identity file was given.
+pub fn run(path: &Path, verify: bool, keys: &Keys<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| format!("cannot read {}: {}", path.display(), e))?
+        .len() as usize;
+    let kind = codec::detect(path)?;
+    println!("File:    {} ({})", path.display(), megabytes(size));
+    println!("Format:  {}", describe(&kind));
+
+    let image = match kind {
+        FileKind::Sqlite => std::fs::read(path)?,
+        FileKind::Compressed(_) => codec::decode_to_memory(path, None, &mut |_| {})?,
+        FileKind::AgePassphrase { .. } | FileKind::AgeRecipients { .. }
+            if verify || keys.password.is_some() || !keys.identities.is_empty() =>
+        {
+            let image = codec::decode_to_memory(path, keys.credentials(&kind)?.as_ref(), &mut super::phase_timer())
+                .map_err(|e| format!("verification failed: {}", e))?;
+            println!(
+                "Decrypted: {} ({:.1}:1 compression)",
+                megabytes(image.len()),
+                image.len() as f64 / size.max(1) as f64
+            );
+            image
+        }
+        FileKind::AgePassphrase { .. } => {
+            println!("Pass --verify (or --password) to decrypt in memory and check the contents");
+            return Ok(());
+        }
+        FileKind::AgeRecipients { .. } => {
+            println!("Pass --verify (or --identity) to decrypt in memory and check the contents");
+            return Ok(());
+        }
+        FileKind::Unknown => return Err("not a database file this tool can open".into()),
+    };
+    verify_image(&image).map_err(|e| format!("verification failed: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::codec;
+use crate::merge::{merge_rows, write_rows};
+use super::{Keys, RecipientArgs};
+use std::path::{Path, PathBuf};
+
+/// Merge the `items` rows of `inputs` into a new database at `output`.
+///
+/// With `encrypt`, the merged database is compressed and encrypted to its
+/// recipients or with the global password (prompted for if not given); this
+/// stages the plaintext result on disk, so it is refused with `in_memory`.
+/// Encrypted inputs are opened with `keys`, as in `open_database`.
+pub async fn run(
+    inputs: &[PathBuf],
+    output: &Path,
+    encrypt: Option<&RecipientArgs>,
+    keys: &Keys<'_>,
+    in_memory: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if in_memory && encrypt.is_some() {
+        return Err(super::IN_MEMORY_ENCRYPT_ERROR.into());
+    }
+    if output.exists() {
+        return Err(format!("output file already exists: {}", output.display()).into());
+    }
+
+    let mut sources = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let opened = super::open_database(input, keys, in_memory).await?;
+        let rows = opened.db.dump_rows().await?;
+        eprintln!("Read {} rows from {}", rows.len(), input.display());
+        sources.push(rows);
+    }
+    let (rows, summary) = merge_rows(sources);
+    let written = rows.len();
+
+    if let Some(recipients) = encrypt {
+        // A directory rather than a file, so the write-ahead log is removed with it
+        let dir = tempfile::tempdir()?;
+        let plain_path = dir.path().join("merged.db");
+        write_rows(&plain_path, rows).await?;
+        let encryption = recipients.encryption(keys.password, &format!("Enter password for {}: ", output.display()))?;
+        codec::encrypt_stream(&plain_path, output, &encryption, &codec::Compression::new(super::ENCRYPT_QUALITY))?;
+    } else {
+        write_rows(output, rows).await?;
+    }
+
+    eprintln!(
+        "Merged {} rows into {} entries in {} ({} duplicates dropped, {} identifiers reassigned)",
+        summary.rows_read,
+        written,
+        output.display(),
+        summary.duplicates,
+        summary.renumbered
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_merge_refuses_encrypt_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("merged.db.age");
+        let recipients = RecipientArgs::default();
+        let err = run(&[], &output, Some(&recipients), &Keys::default(), true).await.unwrap_err();
+        assert_eq!(err.to_string(), crate::cli::IN_MEMORY_ENCRYPT_ERROR);
+        assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! Headless subcommands that print reports instead of starting the TUI.
+
+pub mod cleanup;
+pub mod duplicates;
+pub mod export;
+pub mod import;
+pub mod inspect;
+pub mod merge;
+pub mod search;
+pub mod show;
+pub mod stats;
+pub mod similar;
+
+use crate::annotations::{Annotation, AnnotationKey, AnnotationStore};
+use crate::codec::{self, Credentials, Encryption, FileKind};
+use crate::db::{Database, TranscriptListItem};
+use age::secrecy::Secret;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Brotli quality for commands that encrypt their result, as for `encrypt` without `--fast`/`--best`.
+pub const ENCRYPT_QUALITY: u32 = 6;
+
+/// Why `merge` and `cleanup` refuse `--encrypt` with `--in-memory`: turso has
+/// no way to serialize an in-memory database, so the result would be staged
+/// unencrypted in a temporary directory, which `--in-memory` rules out.
+pub const IN_MEMORY_ENCRYPT_ERROR: &str = "--encrypt cannot be combined with --in-memory: the result has to be \
+     written as a plain database file before it is encrypted; encrypt it separately after writing it to a location you trust";
+
+/// `password`, or one read from the terminal after printing `prompt` to stderr.
+pub fn password_or_prompt(password: Option<&str>, prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    match password {
+        Some(p) => Ok(p.to_string()),
+        None => {
+            eprint!("{}", prompt);
+            crate::read_password_with_stars()
+        }
+    }
+}
+
+/// Options of the commands that write encrypted files.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct RecipientArgs {
+    /// Encrypt to this age public key (age1...) instead of a password; repeatable
+    #[arg(long = "recipient", value_name = "KEY")]
+    pub recipients: Vec<String>,
+    /// Encrypt to the public keys listed in this file, one per line; repeatable
+    #[arg(long = "recipients-file", value_name = "FILE")]
+    pub recipients_files: Vec<PathBuf>,
+}
+
+impl RecipientArgs {
+    pub fn is_empty(&self) -> bool {
+        self.recipients.is_empty() && self.recipients_files.is_empty()
+    }
+
+    /// The recipients if any were given, otherwise `password` (prompted for with `prompt`).
+    pub fn encryption(&self, password: Option<&str>, prompt: &str) -> Result<Encryption, Box<dyn std::error::Error>> {
+        if self.is_empty() {
+            return Ok(Encryption::Passphrase(Secret::new(password_or_prompt(password, prompt)?)));
+        }
+        Ok(Encryption::Recipients(codec::parse_recipients(&self.recipients, &self.recipients_files)?))
+    }
+}
+
+/// What encrypted databases are opened with: the global `--password` and
+/// `--identity` options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keys<'a> {
+    pub password: Option<&'a str>,
+    pub identities: &'a [PathBuf],
+}
+
+impl Keys<'_> {
+    /// Identity files to try: those given, else `identity.txt` in the config directory if it exists.
+    pub fn identity_files(&self) -> Vec<PathBuf> {
+        if !self.identities.is_empty() {
+            return self.identities.to_vec();
+        }
+        default_identity_path().filter(|p| p.exists()).into_iter().collect()
+    }
+
+    /// Credentials for a file of `kind`: identities if it was encrypted to
+    /// recipients, the password (prompted for if not given) for a passphrase,
+    /// and none if it is not encrypted.
+    pub fn credentials(&self, kind: &FileKind) -> Result<Option<Credentials>, Box<dyn std::error::Error>> {
+        Ok(match kind {
+            FileKind::AgeRecipients { .. } => Some(Credentials::Identities(self.identities()?)),
+            FileKind::AgePassphrase { .. } => {
+                let password = password_or_prompt(self.password, "Enter password: ")?;
+                Some(Credentials::Passphrase(Secret::new(password)))
+            }
+            _ => None,
+        })
+    }
+
+    /// The identities of `identity_files`.
+    pub fn identities(&self) -> Result<Vec<age::x25519::Identity>, Box<dyn std::error::Error>> {
+        let files = self.identity_files();
+        if files.is_empty() {
+            return Err("the file is encrypted to recipients; pass --identity with your key file".into());
+        }
+        Ok(codec::read_identities(&files)?)
+    }
+}
+
+/// Default identity file: `<config dir>/identity.txt`, next to `settings.json`.
+pub fn default_identity_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("com", "rocketrecap", "transcript-explorer")
+        .map(|dirs| dirs.config_dir().join("identity.txt"))
+}
+
+/// Progress callback for headless commands: prints on stderr how long each
+/// decoding phase before the last one took (e.g. scrypt key derivation).
+pub fn phase_timer() -> impl FnMut(codec::Progress) {
+    let mut current: Option<(codec::Phase, std::time::Instant)> = None;
+    move |progress| {
+        if current.is_some_and(|(phase, _)| phase == progress.phase) {
+            return;
+        }
+        if let Some((phase, start)) = current {
+            eprintln!("{} took {:?}", phase.label(), start.elapsed());
+        }
+        current = Some((progress.phase, std::time::Instant::now()));
+    }
+}
+
+/// A database opened for a headless command.
+///
+/// Encrypted and compressed files are decoded into memory or to a temporary file that
+/// lives as long as this value.
+pub struct OpenedDatabase {
+    pub db: Database,
+    _temp: Option<tempfile::NamedTempFile>,
+}
+
+/// Open a plain, compressed or age-encrypted database without the TUI.
+///
+/// Encrypted files are opened with `keys` (see `Keys::credentials`).
+/// With `in_memory` they are decoded without writing the plaintext to disk.
+pub async fn open_database(
+    path: &Path,
+    keys: &Keys<'_>,
+    in_memory: bool,
+) -> Result<OpenedDatabase, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Err(format!("database file not found: {}", path.display()).into());
+    }
+
+    let mut temp = None;
+    let kind = codec::detect(path)?;
+    let target = if kind.is_encoded() {
+        let credentials = keys.credentials(&kind)?;
+        if in_memory {
+            let image = codec::decode_to_memory(path, credentials.as_ref(), &mut phase_timer())
+                .map_err(|e| format!("Failed to decode database: {}", e))?;
+            let db = Database::open_image(&image)
+                .await
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            for table in db.skipped_tables() {
+                eprintln!("Warning: table {} is WITHOUT ROWID and was not loaded", table);
+            }
+            return Ok(OpenedDatabase { db, _temp: None });
+        }
+        let file = tempfile::NamedTempFile::new()?;
+        codec::decode_stream(path, file.path(), credentials.as_ref(), &mut phase_timer())
+            .map_err(|e| format!("Failed to decode database: {}", e))?;
+        let target = file.path().to_path_buf();
+        temp = Some(file);
+        target
+    } else {
+        path.to_path_buf()
+    };
+
+    let db = Database::open(&target)
+        .await
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    Ok(OpenedDatabase { db, _temp: temp })
+}
+
+/// All list rows of `db` with bookmarks and tags merged in from the annotation
+/// store, so `tag=` and `bookmarked=` filters behave as in the TUI.
+///
+/// A missing store just means there are no annotations.
+pub async fn load_items(db: &Database) -> Result<Vec<TranscriptListItem>, Box<dyn std::error::Error>> {
+    let mut items = db.list_all_transcripts().await?;
+    let annotations = load_annotations().await?;
+    for item in &mut items {
+        if let Some(a) = annotations.get(&(item.identifier, item.original_source_link.clone())) {
+            item.bookmarked = a.bookmarked;
+            item.tags = a.tags.clone();
+        }
+    }
+    Ok(items)
+}
+
+/// Every annotation in the default store; a missing store means none.
+pub async fn load_annotations() -> Result<HashMap<AnnotationKey, Annotation>, Box<dyn std::error::Error>> {
+    match AnnotationStore::default_path().filter(|p| p.exists()) {
+        Some(path) => Ok(AnnotationStore::open(&path).await?.load_all().await?),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Database with three transcripts for the subcommand tests: 1 and 3 share a
+/// summary about Rust, 2 talks about tokio in its transcript. The embeddings
+/// of 1 and 3 point in nearly the same direction, that of 2 is orthogonal.
+#[cfg(test)]
+pub async fn test_database() -> (tempfile::TempDir, Database) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cli.db");
+    let conn = turso::Builder::new_local(&path.to_string_lossy()).build().await.unwrap().connect().unwrap();
+    conn.execute(
+        "CREATE TABLE items (identifier INTEGER PRIMARY KEY, model TEXT, host TEXT, \
+         original_source_link TEXT, output_language TEXT, summary TEXT, summary_done INTEGER, \
+         summary_input_tokens INTEGER, summary_output_tokens INTEGER, transcript TEXT, \
+         timestamps TEXT, timestamped_summary_in_youtube_format TEXT, cost REAL, embedding BLOB, \
+         embedding_model TEXT, summary_timestamp_start TEXT, summary_timestamp_end TEXT)",
+        (),
+    )
+    .await
+    .unwrap();
+    conn.execute(
+        "INSERT INTO items (identifier, model, host, original_source_link, output_language, summary, \
+         summary_input_tokens, summary_output_tokens, transcript, timestamps, cost) VALUES \
+         (1, 'gemini-pro', 'h1', 'https://youtu.be/a', 'en', '**Learning Rust**\nOwnership.', 100, 10, 'borrowing', '0:00 Intro', 0.5), \
+         (2, 'gemini-flash', 'h2', 'https://youtu.be/b', 'de', '**Async**\nRuntimes.', 200, 20, 'tokio tasks', '', 0.25), \
+         (3, 'gemini-flash', 'h1', 'https://youtu.be/c', 'en', '**Learning Rust**\nOwnership.', 300, 30, '', '', 1.0)",
+        (),
+    )
+    .await
+    .unwrap();
+    for (id, x, y) in [(1, 1.0f32, 0.0f32), (2, 0.0, 1.0), (3, 1.0, 0.1)] {
+        let mut v = vec![0.0f32; crate::db::SIMILARITY_DIMS];
+        v[..2].copy_from_slice(&[x, y]);
+        let v: Vec<String> = v.iter().map(|f| f.to_string()).collect();
+        let sql = format!("UPDATE items SET embedding = vector32('[{}]') WHERE identifier = {}", v.join(","), id);
+        conn.execute(&sql, ()).await.unwrap();
+    }
+    let db = Database::open(&path).await.unwrap();
+    (dir, db)
+}
@@ -0,0 +1,178 @@
+use crate::app::get_display_title;
+use crate::db::{Database, TranscriptListItem};
+use crate::query;
+use crate::ui::list::truncate_str;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Width of the title column in table output.
+const TITLE_WIDTH: usize = 60;
+
+/// Options of the `search` subcommand.
+pub struct SearchArgs {
+    /// Free-text filter, or the ranked query with `fulltext`.
+    pub text: String,
+    /// Filter expressions, combined with AND.
+    pub filters: Vec<String>,
+    /// Rank by BM25 over summaries, transcripts and timestamps instead of substring matching.
+    pub fulltext: bool,
+    /// Collapse consecutive entries with identical summaries, as the list does.
+    pub group: bool,
+    pub limit: Option<usize>,
+    /// JSON lines instead of a table.
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct JsonRow<'a> {
+    identifier: i64,
+    title: String,
+    link: &'a str,
+    host: &'a str,
+    model: &'a str,
+    cost: f64,
+    bookmarked: bool,
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_size: Option<usize>,
+}
+
+/// Exit status of `search`: 0 when something matched, 1 when nothing did, 2 on errors.
+pub fn exit_code<E>(result: &Result<bool, E>) -> i32 {
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(_) => 2,
+    }
+}
+
+/// Print the rows of `db` matching the query to `out`. Returns whether anything matched.
+pub async fn run(db: &Database, args: &SearchArgs, out: &mut impl Write) -> Result<bool, Box<dyn std::error::Error>> {
+    let filter = query::parse_filters(&args.filters)?;
+    let items = super::load_items(db).await?;
+
+    let mut scores: HashMap<i64, f64> = HashMap::new();
+    let selected: Vec<&TranscriptListItem> = if args.fulltext {
+        if args.text.trim().is_empty() {
+            return Err("--fulltext needs a query".into());
+        }
+        let by_id: HashMap<i64, &TranscriptListItem> = items.iter().map(|i| (i.identifier, i)).collect();
+        db.search_fulltext(&args.text, items.len())
+            .await?
+            .into_iter()
+            .filter_map(|hit| {
+                scores.insert(hit.identifier, hit.score);
+                by_id.get(&hit.identifier).copied()
+            })
+            .filter(|item| filter.as_ref().is_none_or(|f| f.matches(item)))
+            .collect()
+    } else {
+        query::select(&items, &args.text, filter.as_ref())
+    };
+
+    let groups: Vec<Vec<&TranscriptListItem>> = if args.group {
+        query::group_consecutive(selected)
+    } else {
+        selected.into_iter().map(|item| vec![item]).collect()
+    };
+    let limit = args.limit.unwrap_or(usize::MAX);
+
+    if !args.json && !groups.is_empty() {
+        let score_header = if args.fulltext { "   SCORE" } else { "" };
+        writeln!(out, "{:>6}{}  {:<20} {:>8}  {:<w$}  LINK", "ID", score_header, "MODEL", "COST", "TITLE", w = TITLE_WIDTH)?;
+    }
+    for group in groups.iter().take(limit) {
+        let item = group[0];
+        let score = scores.get(&item.identifier).copied();
+        let group_size = args.group.then_some(group.len());
+        if args.json {
+            let row = JsonRow {
+                identifier: item.identifier,
+                title: get_display_title(&item.summary),
+                link: &item.original_source_link,
+                host: &item.host,
+                model: &item.model,
+                cost: item.cost,
+                bookmarked: item.bookmarked,
+                tags: &item.tags,
+                score,
+                group_size,
+            };
+            writeln!(out, "{}", serde_json::to_string(&row)?)?;
+        } else {
+            let mut title = get_display_title(&item.summary);
+            if let Some(n) = group_size.filter(|n| *n > 1) {
+                title = format!("({}×) {}", n, title);
+            }
+            let score = score.map(|s| format!(" {:>7.2}", s)).unwrap_or_default();
+            writeln!(
+                out,
+                "{:>6}{}  {:<20} {:>8.4}  {:<w$}  {}",
+                item.identifier,
+                score,
+                truncate_str(&item.model, 20),
+                item.cost,
+                truncate_str(&title, TITLE_WIDTH),
+                item.original_source_link,
+                w = TITLE_WIDTH
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(!groups.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_database;
+
+    fn args(text: &str) -> SearchArgs {
+        SearchArgs { text: text.to_string(), filters: Vec::new(), fulltext: false, group: false, limit: None, json: false }
+    }
+
+    async fn search(args: &SearchArgs) -> (Result<bool, Box<dyn std::error::Error>>, String) {
+        let (_dir, db) = test_database().await;
+        let mut out = Vec::new();
+        let result = run(&db, args, &mut out).await;
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_search_table() {
+        let (result, out) = search(&args("rust")).await;
+        assert_eq!(exit_code(&result), 0);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("    ID  MODEL"));
+        assert!(lines[1].starts_with("     1  gemini"));
+        assert!(lines[1].ends_with("https://youtu.be/a"));
+    }
+
+    #[tokio::test]
+    async fn test_search_json_lines_with_scores() {
+        let (result, out) = search(&SearchArgs { fulltext: true, json: true, limit: Some(1), ..args("tokio") }).await;
+        assert!(result.unwrap());
+        let lines: Vec<serde_json::Value> = out.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["identifier"], 2);
+        assert_eq!(lines[0]["link"], "https://youtu.be/b");
+        assert!(lines[0]["score"].as_f64().unwrap() > 0.0);
+        assert!(lines[0].get("group_size").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_exit_codes() {
+        let (result, out) = search(&args("no such words")).await;
+        assert_eq!(exit_code(&result), 1);
+        assert!(out.is_empty());
+
+        let (result, _) = search(&SearchArgs { filters: vec!["cost=abc".to_string()], ..args("") }).await;
+        assert_eq!(exit_code(&result), 2);
+        let (result, _) = search(&SearchArgs { fulltext: true, ..args(" ") }).await;
+        assert_eq!(exit_code(&result), 2);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::annotations::Annotation;
+use crate::app::{get_display_title, DetailTab};
+use crate::db::{Database, TranscriptRow};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+
+/// Output format of the `show` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShowFormat {
+    /// Metadata header followed by the sections
+    Text,
+    Markdown,
+    Json,
+}
+
+/// Options of the `show` subcommand.
+pub struct ShowArgs {
+    pub id: i64,
+    /// Sections to print, in order.
+    pub sections: Vec<DetailTab>,
+    pub format: ShowFormat,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    identifier: i64,
+    title: String,
+    model: &'a str,
+    host: &'a str,
+    link: &'a str,
+    language: &'a str,
+    cost: f64,
+    input_tokens: i64,
+    output_tokens: i64,
+    embedding_model: &'a str,
+    bookmarked: bool,
+    tags: &'a [String],
+    note: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transcript: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamps: Option<&'a str>,
+}
+
+/// Print one transcript with its metadata to `out`.
+pub async fn run(db: &Database, args: &ShowArgs, out: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+    let row = db
+        .get_transcript(args.id)
+        .await?
+        .ok_or_else(|| format!("no transcript with identifier {}", args.id))?;
+    let annotation = super::load_annotations()
+        .await?
+        .remove(&(row.identifier, row.original_source_link.clone()))
+        .unwrap_or_default();
+
+    match args.format {
+        ShowFormat::Text => write_text(out, &row, &annotation, &args.sections)?,
+        ShowFormat::Markdown => write_markdown(out, &row, &annotation, &args.sections)?,
+        ShowFormat::Json => {
+            let section = |tab: DetailTab| args.sections.contains(&tab).then(|| tab.content(&row));
+            let entry = JsonEntry {
+                identifier: row.identifier,
+                title: get_display_title(&row.summary),
+                model: &row.model,
+                host: &row.host,
+                link: &row.original_source_link,
+                language: &row.output_language,
+                cost: row.cost,
+                input_tokens: row.summary_input_tokens,
+                output_tokens: row.summary_output_tokens,
+                embedding_model: &row.embedding_model,
+                bookmarked: annotation.bookmarked,
+                tags: &annotation.tags,
+                note: &annotation.note,
+                summary: section(DetailTab::Summary),
+                transcript: section(DetailTab::Transcript),
+                timestamps: section(DetailTab::Timestamps),
+            };
+            writeln!(out, "{}", serde_json::to_string_pretty(&entry)?)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn dash(s: &str) -> &str {
+    if s.is_empty() { "-" } else { s }
+}
+
+/// Same fields as the detail view's metadata header.
+fn write_text(
+    out: &mut impl Write,
+    row: &TranscriptRow,
+    annotation: &Annotation,
+    sections: &[DetailTab],
+) -> std::io::Result<()> {
+    let tags: Vec<String> = annotation.tags.iter().map(|t| format!("#{}", t)).collect();
+    writeln!(out, "ID: {}   Model: {}   Cost: ${:.4}", row.identifier, row.model, row.cost)?;
+    writeln!(out, "Host: {}", row.host)?;
+    writeln!(
+        out,
+        "Link: {}   Lang: {}   Tokens: {}in/{}out",
+        row.original_source_link, row.output_language, row.summary_input_tokens, row.summary_output_tokens
+    )?;
+    writeln!(
+        out,
+        "{} Tags: {}   Note: {}",
+        if annotation.bookmarked { "★" } else { "☆" },
+        dash(&tags.join(" ")),
+        dash(&annotation.note)
+    )?;
+    for tab in sections {
+        writeln!(out, "\n── {} ──\n", tab.label())?;
+        writeln!(out, "{}", tab.content(row).trim_end())?;
+    }
+    Ok(())
+}
+
+fn write_markdown(
+    out: &mut impl Write,
+    row: &TranscriptRow,
+    annotation: &Annotation,
+    sections: &[DetailTab],
+) -> std::io::Result<()> {
+    writeln!(out, "# {}\n", get_display_title(&row.summary))?;
+    writeln!(out, "- **ID**: {}", row.identifier)?;
+    writeln!(out, "- **Link**: <{}>", row.original_source_link)?;
+    writeln!(out, "- **Model**: {} (${:.4})", row.model, row.cost)?;
+    writeln!(out, "- **Host**: {}", dash(&row.host))?;
+    writeln!(out, "- **Language**: {}", dash(&row.output_language))?;
+    writeln!(out, "- **Tokens**: {} in / {} out", row.summary_input_tokens, row.summary_output_tokens)?;
+    if annotation.bookmarked {
+        writeln!(out, "- **Bookmarked**")?;
+    }
+    if !annotation.tags.is_empty() {
+        writeln!(out, "- **Tags**: {}", annotation.tags.join(", "))?;
+    }
+    if !annotation.note.is_empty() {
+        writeln!(out, "- **Note**: {}", annotation.note)?;
+    }
+    for tab in sections {
+        writeln!(out, "\n## {}\n", tab.label())?;
+        writeln!(out, "{}", tab.content(row).trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_database;
+
+    async fn show(id: i64, sections: &[DetailTab], format: ShowFormat) -> Result<String, Box<dyn std::error::Error>> {
+        let (_dir, db) = test_database().await;
+        let mut out = Vec::new();
+        run(&db, &ShowArgs { id, sections: sections.to_vec(), format }, &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_show_text() {
+        let out = show(1, &[DetailTab::Transcript, DetailTab::Timestamps], ShowFormat::Text).await.unwrap();
+        assert!(out.starts_with("ID: 1   Model: gemini-pro   Cost: $0.5000\nHost: h1\n"));
+        assert!(out.contains("Link: https://youtu.be/a   Lang: en   Tokens: 100in/10out\n"));
+        assert!(out.ends_with("\n── Transcript ──\n\nborrowing\n\n── Timestamps ──\n\n0:00 Intro\n"));
+    }
+
+    #[tokio::test]
+    async fn test_show_markdown() {
+        let out = show(2, &[DetailTab::Summary], ShowFormat::Markdown).await.unwrap();
+        assert!(out.starts_with("# **Async**\n\n- **ID**: 2\n- **Link**: <https://youtu.be/b>\n"));
+        assert!(out.ends_with("\n## Summary\n\n**Async**\nRuntimes.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_show_json_only_has_requested_sections() {
+        let out = show(3, &[DetailTab::Summary], ShowFormat::Json).await.unwrap();
+        let entry: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(entry["identifier"], 3);
+        assert_eq!(entry["output_tokens"], 30);
+        assert_eq!(entry["summary"], "**Learning Rust**\nOwnership.");
+        assert!(entry.get("transcript").is_none());
+        assert!(entry.get("timestamps").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_show_missing_identifier() {
+        let err = show(9, &[], ShowFormat::Text).await.unwrap_err();
+        assert_eq!(err.to_string(), "no transcript with identifier 9");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::app::get_display_title;
+use crate::db::{Database, SimilarityQuery};
+use crate::vector::Metric;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+
+/// Output format of the `similar` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SimilarFormat {
+    /// Tab-separated: distance, identifier, link, title
+    Tsv,
+    /// A JSON array of neighbours
+    Json,
+}
+
+/// Options of the `similar` subcommand.
+pub struct SimilarArgs {
+    pub id: i64,
+    pub limit: usize,
+    pub metric: Metric,
+    pub max_distance: Option<f64>,
+    pub format: SimilarFormat,
+}
+
+#[derive(Serialize)]
+struct JsonNeighbour<'a> {
+    identifier: i64,
+    distance: f64,
+    /// `1 - distance`, cosine only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    similarity: Option<f64>,
+    link: &'a str,
+    title: String,
+}
+
+/// Print the nearest neighbours of transcript `args.id` to `out`.
+pub async fn run(db: &Database, args: &SimilarArgs, out: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(max) = args.max_distance.filter(|d| !args.metric.valid_cutoff(*d)) {
+        return Err(format!("invalid --max-distance {} for the {} metric", max, args.metric.label()).into());
+    }
+    if !db.schema().has_embeddings() {
+        return Err("this database has no embeddings".into());
+    }
+    match db.get_transcript(args.id).await? {
+        None => return Err(format!("no transcript with identifier {}", args.id).into()),
+        Some(row) if !row.has_embedding => {
+            return Err(format!("transcript {} has no embedding", args.id).into());
+        }
+        Some(_) => {}
+    }
+
+    let query = SimilarityQuery {
+        metric: args.metric,
+        max_distance: args.max_distance,
+        limit: args.limit as i64,
+        ..SimilarityQuery::default()
+    };
+    let results = db.find_similar(args.id, &query).await?;
+
+    match args.format {
+        SimilarFormat::Tsv => {
+            for r in &results {
+                // Tabs and line breaks would split fields.
+                let title = get_display_title(&r.summary).replace(['\t', '\n', '\r'], " ");
+                writeln!(out, "{:.6}\t{}\t{}\t{}", r.distance, r.identifier, r.original_source_link, title)?;
+            }
+        }
+        SimilarFormat::Json => {
+            let neighbours: Vec<JsonNeighbour> = results
+                .iter()
+                .map(|r| JsonNeighbour {
+                    identifier: r.identifier,
+                    distance: r.distance,
+                    similarity: args.metric.similarity(r.distance),
+                    link: &r.original_source_link,
+                    title: get_display_title(&r.summary),
+                })
+                .collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&neighbours)?)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_database;
+
+    fn args(format: SimilarFormat) -> SimilarArgs {
+        SimilarArgs { id: 1, limit: 10, metric: Metric::Cosine, max_distance: None, format }
+    }
+
+    async fn similar(args: &SimilarArgs) -> Result<String, Box<dyn std::error::Error>> {
+        let (_dir, db) = test_database().await;
+        let mut out = Vec::new();
+        run(&db, args, &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_similar_tsv() {
+        let out = similar(&args(SimilarFormat::Tsv)).await.unwrap();
+        let rows: Vec<Vec<&str>> = out.lines().map(|l| l.split('\t').collect()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][1..], ["3", "https://youtu.be/c", "**Learning Rust**"]);
+        assert_eq!(rows[1][1], "2");
+        assert!(rows[0][0].parse::<f64>().unwrap() < rows[1][0].parse::<f64>().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_similar_json_and_cutoff() {
+        let out = similar(&SimilarArgs { max_distance: Some(0.5), ..args(SimilarFormat::Json) }).await.unwrap();
+        let neighbours: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(neighbours.len(), 1);
+        assert_eq!(neighbours[0]["identifier"], 3);
+        assert_eq!(neighbours[0]["title"], "**Learning Rust**");
+        let distance = neighbours[0]["distance"].as_f64().unwrap();
+        assert!((neighbours[0]["similarity"].as_f64().unwrap() - (1.0 - distance)).abs() < 1e-9);
+
+        let out = similar(&SimilarArgs { metric: Metric::L2, ..args(SimilarFormat::Json) }).await.unwrap();
+        let neighbours: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert!(neighbours[0].get("similarity").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_similar_errors() {
+        let err = similar(&SimilarArgs { id: 9, ..args(SimilarFormat::Tsv) }).await.unwrap_err();
+        assert_eq!(err.to_string(), "no transcript with identifier 9");
+        let err = similar(&SimilarArgs { max_distance: Some(-1.0), ..args(SimilarFormat::Tsv) }).await.unwrap_err();
+        assert!(err.to_string().starts_with("invalid --max-distance"));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::db::{Database, TranscriptListItem};
+use crate::query;
+use crate::stats::{self, breakdown, GroupBy, GroupStats, Stats};
+use crate::ui::list::truncate_str;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Width of the group key column in table output.
+const KEY_WIDTH: usize = 28;
+
+/// Options of the `stats` subcommand.
+pub struct StatsArgs {
+    /// Free-text filter, as typed after `/` in the TUI.
+    pub text: String,
+    /// Filter expressions, combined with AND.
+    pub filters: Vec<String>,
+    /// Breakdowns to report, in order.
+    pub by: Vec<GroupBy>,
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct JsonReport {
+    entries: usize,
+    total_cost: f64,
+    overall: BTreeMap<&'static str, Stats>,
+    groups: BTreeMap<&'static str, Vec<GroupStats>>,
+}
+
+/// Print cost and token statistics of the matching rows to `out`, overall and per group.
+pub async fn run(db: &Database, args: &StatsArgs, out: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = query::parse_filters(&args.filters)?;
+    let items = super::load_items(db).await?;
+    let selected = query::select(&items, &args.text, filter.as_ref());
+    let total_cost: f64 = selected.iter().map(|i| i.cost).sum();
+    let overall: BTreeMap<&'static str, Stats> = stats::FIELDS
+        .iter()
+        .map(|&field| (field, Stats::from_values(selected.iter().map(|i| stats::field_value(i, field)).collect())))
+        .collect();
+
+    if args.json {
+        let report = JsonReport {
+            entries: selected.len(),
+            total_cost,
+            overall,
+            groups: args.by.iter().map(|&by| (by.label(), breakdown(selected.iter().copied(), by))).collect(),
+        };
+        writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?;
+        return Ok(());
+    }
+
+    writeln!(out, "{} entries, total cost ${:.4}", selected.len(), total_cost)?;
+    for (field, s) in &overall {
+        writeln!(
+            out,
+            "  {:<14} mean {:>12.4}  median {:>12.4}  MAD {:>12.4}  p5 {:>12.4}  p95 {:>12.4}",
+            field, s.mean, s.median, s.mad, s.p5, s.p95
+        )?;
+    }
+    for &by in &args.by {
+        writeln!(out)?;
+        write_table(out, by, &selected)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn write_table(out: &mut impl Write, by: GroupBy, items: &[&TranscriptListItem]) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{:<w$} {:>6} {:>11} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        by.label().to_uppercase(),
+        "COUNT",
+        "TOTAL COST",
+        "MEAN",
+        "MEDIAN",
+        "MAD",
+        "P5",
+        "P95",
+        "IN TOK",
+        "OUT TOK",
+        w = KEY_WIDTH
+    )?;
+    for g in breakdown(items.iter().copied(), by) {
+        writeln!(
+            out,
+            "{:<w$} {:>6} {:>11.4} {:>9.4} {:>9.4} {:>9.4} {:>9.4} {:>9.4} {:>9.0} {:>9.0}",
+            truncate_str(&g.key, KEY_WIDTH),
+            g.count,
+            g.total_cost,
+            g.cost.mean,
+            g.cost.median,
+            g.cost.mad,
+            g.cost.p5,
+            g.cost.p95,
+            g.input_tokens.mean,
+            g.output_tokens.mean,
+            w = KEY_WIDTH
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_database;
+
+    async fn stats(args: &StatsArgs) -> Result<String, Box<dyn std::error::Error>> {
+        let (_dir, db) = test_database().await;
+        let mut out = Vec::new();
+        run(&db, args, &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_stats_table() {
+        let args = StatsArgs { text: String::new(), filters: Vec::new(), by: vec![GroupBy::Host], json: false };
+        let out = stats(&args).await.unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "3 entries, total cost $1.7500");
+        assert!(lines[1].starts_with("  cost           mean       0.5833  median       0.5000"));
+        assert!(lines[5].starts_with("HOST "));
+        let h1: Vec<&str> = lines.iter().find(|l| l.starts_with("h1 ")).unwrap().split_whitespace().collect();
+        assert_eq!(h1[..3], ["h1", "2", "1.5000"]);
+    }
+
+    #[tokio::test]
+    async fn test_stats_json_with_filter() {
+        let args = StatsArgs {
+            text: String::new(),
+            filters: vec!["model=gemini-flash".to_string()],
+            by: vec![GroupBy::Language],
+            json: true,
+        };
+        let report: serde_json::Value = serde_json::from_str(&stats(&args).await.unwrap()).unwrap();
+        assert_eq!(report["entries"], 2);
+        assert_eq!(report["total_cost"], 1.25);
+        assert_eq!(report["overall"]["output_tokens"]["count"], 2);
+        let languages: Vec<&str> = report["groups"]["language"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|g| g["key"].as_str().unwrap())
+            .collect();
+        assert_eq!(languages.len(), 2);
+        assert!(languages.contains(&"de") && languages.contains(&"en"));
+    }
+
+    #[tokio::test]
+    async fn test_stats_rejects_bad_filter() {
+        let args = StatsArgs { text: String::new(), filters: vec!["cost=abc".to_string()], by: Vec::new(), json: false };
+        assert!(stats(&args).await.is_err());
+    }
+}
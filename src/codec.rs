@@ -1,79 +1,748 @@
+use std::cell::Cell;
 use std::fs::File;
-use std::io::{self, Write};
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use age::secrecy::{Secret, ExposeSecret};
+use brotli::concat::{BroCatli, BroCatliResult};
 use brotli::enc::BrotliEncoderParams;
+use clap::ValueEnum;
+use rayon::prelude::*;
+use crate::sqlite_file::SQLITE_MAGIC;
+
+/// Header every age-encrypted file starts with.
+const AGE_MAGIC: &[u8; 18] = b"age-encryption.org";
+
+/// Bytes read to classify a file; the age header and the start of a Brotli stream fit easily.
+const PROBE_SIZE: u64 = 64 * 1024;
+
+const BUFFER_SIZE: usize = 65536;
+
+/// First bytes of a zstd frame.
+const ZSTD_MAGIC: &[u8; 4] = &[0x28, 0xB5, 0x2F, 0xFD];
+/// First bytes of a gzip member.
+const GZIP_MAGIC: &[u8; 2] = &[0x1F, 0x8B];
+
+/// Compression of a database, inside age or on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Codec {
+    /// Smallest files; compressed in parallel chunks
+    Brotli,
+    /// Much faster to decode, slightly larger
+    Zstd,
+    /// For tools that only know gzip
+    Gzip,
+}
+
+impl Codec {
+    /// The codec whose magic bytes `data` starts with. Brotli has none, so it is never returned.
+    fn from_magic(data: &[u8]) -> Option<Codec> {
+        if data.starts_with(ZSTD_MAGIC) {
+            Some(Codec::Zstd)
+        } else if data.starts_with(GZIP_MAGIC) {
+            Some(Codec::Gzip)
+        } else {
+            None
+        }
+    }
+
+    /// Quality for `--fast`, by default and for `--best`.
+    pub fn qualities(self) -> [u32; 3] {
+        match self {
+            Codec::Brotli => [1, 6, 11],
+            Codec::Zstd => [1, 3, 19],
+            Codec::Gzip => [1, 6, 9],
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Codec::Brotli => "Brotli",
+            Codec::Zstd => "zstd",
+            Codec::Gzip => "gzip",
+        }
+    }
+}
+
+/// Container of a database file, judged from its first bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileKind {
+    /// A plain SQLite database
+    Sqlite,
+    /// age with a passphrase; `work_factor` is scrypt's log2(N)
+    AgePassphrase { work_factor: u8 },
+    /// age for public-key recipients, with the stanza type of each (e.g. `X25519`)
+    AgeRecipients { stanzas: Vec<String> },
+    /// Compressed but not encrypted: zstd or gzip by their magic bytes, or a
+    /// Brotli stream that decodes to a SQLite header
+    Compressed(Codec),
+    Unknown,
+}
+
+impl FileKind {
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, FileKind::AgePassphrase { .. } | FileKind::AgeRecipients { .. })
+    }
+
+    /// Whether the database must be decrypted or decompressed before SQLite can open it.
+    pub fn is_encoded(&self) -> bool {
+        self.is_encrypted() || matches!(self, FileKind::Compressed(_))
+    }
+}
+
+/// Classify the file at `path` without decrypting it.
+///
+/// The codec inside an age file is only known after decrypting; see `decode_stream`.
+pub fn detect(path: &Path) -> io::Result<FileKind> {
+    let mut prefix = Vec::new();
+    File::open(path)?.take(PROBE_SIZE).read_to_end(&mut prefix)?;
+    if prefix.starts_with(SQLITE_MAGIC) {
+        return Ok(FileKind::Sqlite);
+    }
+    if prefix.starts_with(AGE_MAGIC) {
+        return Ok(parse_age_header(&prefix));
+    }
+    if let Some(codec) = Codec::from_magic(&prefix) {
+        return Ok(FileKind::Compressed(codec));
+    }
+    let mut decoded = [0u8; 16];
+    let is_brotli = brotli::Decompressor::new(&prefix[..], 4096)
+        .read_exact(&mut decoded)
+        .is_ok_and(|_| &decoded == SQLITE_MAGIC);
+    Ok(if is_brotli { FileKind::Compressed(Codec::Brotli) } else { FileKind::Unknown })
+}
+
+/// Recipient stanzas (`-> type args...`) of an age header, up to the `---` MAC line.
+fn parse_age_header(prefix: &[u8]) -> FileKind {
+    let text = String::from_utf8_lossy(prefix);
+    let mut stanzas = Vec::new();
+    for line in text.lines().skip(1).take_while(|l| !l.starts_with("---")) {
+        let Some(args) = line.strip_prefix("-> ") else {
+            continue;
+        };
+        let args: Vec<&str> = args.split_whitespace().collect();
+        // A scrypt stanza is always the only one: `-> scrypt <salt> <log2 N>`
+        if let ["scrypt", _, work_factor] = args[..]
+            && let Ok(work_factor) = work_factor.parse()
+        {
+            return FileKind::AgePassphrase { work_factor };
+        }
+        // Skip the random "grease" stanzas age adds to keep parsers lenient
+        if let Some(kind) = args.first()
+            && !kind.ends_with("-grease")
+        {
+            stanzas.push(kind.to_string());
+        }
+    }
+    FileKind::AgeRecipients { stanzas }
+}
+
+/// True if the file at `path` starts with the age header.
+pub fn is_age_file(path: &Path) -> bool {
+    let mut buffer = [0u8; 18];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut buffer))
+        .is_ok_and(|_| &buffer == AGE_MAGIC)
+}
+
+/// Default input bytes per independently compressed chunk.
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// How `encrypt_stream` compresses.
+///
+/// For Brotli the input is cut into chunks that are compressed in parallel,
+/// then joined into one standard Brotli stream, so the output decodes like a
+/// single-threaded one (also with older versions of this tool). zstd runs its
+/// own worker threads; gzip is single-threaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub codec: Codec,
+    /// Codec quality: 0-11 for Brotli, 1-22 for zstd, 0-9 for gzip
+    pub quality: u32,
+    /// Input bytes per Brotli chunk; larger chunks compress slightly better but use more memory
+    pub chunk_size: usize,
+    /// Worker threads; 0 uses one per CPU
+    pub threads: usize,
+}
+
+impl Compression {
+    /// Brotli at `quality`.
+    pub fn new(quality: u32) -> Self {
+        Self { codec: Codec::Brotli, quality, chunk_size: DEFAULT_CHUNK_SIZE, threads: 0 }
+    }
+}
+
+/// Compress one chunk so that `BroCatli` can join it to the previous ones.
+fn compress_chunk(chunk: &[u8], quality: u32, first: bool) -> io::Result<Vec<u8>> {
+    let params = BrotliEncoderParams {
+        quality: quality as i32,
+        // The first chunk may be followed by others; the others may follow one
+        appendable: first,
+        catable: !first,
+        use_dictionary: first,
+        ..Default::default()
+    };
+    let mut out = Vec::with_capacity(chunk.len() / 4);
+    {
+        let mut compressor = brotli::CompressorWriter::with_params(&mut out, BUFFER_SIZE, &params);
+        compressor.write_all(chunk)?;
+        compressor.flush()?;
+    }
+    Ok(out)
+}
+
+/// Feed one compressed chunk through `catli` into `output`.
+fn write_joined(catli: &mut BroCatli, chunk: &[u8], buffer: &mut [u8], output: &mut impl Write) -> io::Result<()> {
+    catli.new_brotli_file();
+    let mut read = 0;
+    loop {
+        let mut written = 0;
+        let result = catli.stream(chunk, &mut read, buffer, &mut written);
+        output.write_all(&buffer[..written])?;
+        match result {
+            BroCatliResult::NeedsMoreOutput => {}
+            BroCatliResult::NeedsMoreInput => return Ok(()),
+            other => return Err(io::Error::other(format!("cannot join Brotli chunks: {:?}", other))),
+        }
+    }
+}
+
+/// Brotli-compress `input` into `output` in parallel chunks joined into one stream.
+fn compress_parallel(mut input: impl Read, output: &mut impl Write, compression: &Compression) -> io::Result<()> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(compression.threads)
+        .build()
+        .map_err(io::Error::other)?;
+    let chunk_size = compression.chunk_size.max(1);
+    let mut catli = BroCatli::new();
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    let mut first = true;
+    loop {
+        // Read one chunk per thread, compress them together, write them in order
+        let mut batch = Vec::with_capacity(pool.current_num_threads());
+        while batch.len() < pool.current_num_threads() {
+            let mut chunk = Vec::with_capacity(chunk_size);
+            (&mut input).take(chunk_size as u64).read_to_end(&mut chunk)?;
+            // An empty input still needs one (empty) stream
+            if chunk.is_empty() && !(first && batch.is_empty()) {
+                break;
+            }
+            batch.push(chunk);
+        }
+        if batch.is_empty() {
+            break;
+        }
+        let compressed: Vec<Vec<u8>> = pool.install(|| {
+            batch
+                .par_iter()
+                .enumerate()
+                .map(|(i, chunk)| compress_chunk(chunk, compression.quality, first && i == 0))
+                .collect::<io::Result<_>>()
+        })?;
+        first = false;
+        for chunk in &compressed {
+            write_joined(&mut catli, chunk, &mut buffer, output)?;
+        }
+        if batch.last().is_none_or(|c| c.len() < chunk_size) {
+            break;
+        }
+    }
+    loop {
+        let mut written = 0;
+        let result = catli.finish(&mut buffer, &mut written);
+        output.write_all(&buffer[..written])?;
+        match result {
+            BroCatliResult::Success => return Ok(()),
+            BroCatliResult::NeedsMoreOutput => {}
+            other => return Err(io::Error::other(format!("cannot join Brotli chunks: {:?}", other))),
+        }
+    }
+}
+
+/// Who can open an encrypted file.
+pub enum Encryption {
+    Passphrase(Secret<String>),
+    /// X25519 public keys (`age1...`); any of their identities decrypts
+    Recipients(Vec<age::x25519::Recipient>),
+}
+
+/// What an encrypted file is opened with.
+pub enum Credentials {
+    Passphrase(Secret<String>),
+    /// X25519 secret keys; the file must be encrypted to one of them
+    Identities(Vec<age::x25519::Identity>),
+}
+
+/// Recipients given as `age1...` keys and as recipients files (one key per
+/// line, `#` comments and blank lines ignored).
+pub fn parse_recipients(keys: &[String], files: &[PathBuf]) -> Result<Vec<age::x25519::Recipient>, String> {
+    let mut lines: Vec<(String, String)> = keys.iter().map(|k| ("--recipient".to_string(), k.clone())).collect();
+    for file in files {
+        let text = std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file.display(), e))?;
+        lines.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(|l| (file.display().to_string(), l.to_string())),
+        );
+    }
+    lines
+        .into_iter()
+        .map(|(source, key)| {
+            key.parse()
+                .map_err(|e| format!("invalid recipient \"{}\" in {}: {}", key, source, e))
+        })
+        .collect()
+}
+
+/// X25519 identities from age identity files (as written by `age-keygen`).
+pub fn read_identities(files: &[PathBuf]) -> Result<Vec<age::x25519::Identity>, String> {
+    let mut identities = Vec::new();
+    for file in files {
+        let parsed = age::IdentityFile::from_file(file.to_string_lossy().into_owned())
+            .map_err(|e| format!("cannot read identity file {}: {}", file.display(), e))?;
+        for entry in parsed.into_identities() {
+            let age::IdentityFileEntry::Native(identity) = entry;
+            identities.push(identity);
+        }
+    }
+    if identities.is_empty() {
+        return Err("no identities found".to_string());
+    }
+    Ok(identities)
+}
+
+/// Compress `input` into `output` as `compression` says.
+fn compress(mut input: impl Read, output: &mut impl Write, compression: &Compression) -> io::Result<()> {
+    match compression.codec {
+        Codec::Brotli => compress_parallel(input, output, compression),
+        Codec::Zstd => {
+            let threads = match compression.threads {
+                0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+                n => n,
+            };
+            let mut encoder = zstd::stream::write::Encoder::new(output, compression.quality as i32)?;
+            encoder.multithread(threads as u32)?;
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Codec::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::new(compression.quality));
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
 
 pub fn encrypt_stream(
     input_path: &Path,
     output_path: &Path,
-    password: Secret<String>,
-    quality: u32,
+    encryption: &Encryption,
+    compression: &Compression,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let start_total = std::time::Instant::now();
     
     let input_file = File::open(input_path)?;
-    let mut input_reader = io::BufReader::new(input_file);
+    let input_reader = io::BufReader::new(input_file);
     
     let output_file = File::create(output_path)?;
     let output_writer = io::BufWriter::with_capacity(65536, output_file);
     
     let start_enc = std::time::Instant::now();
-    let encryptor = age::Encryptor::with_user_passphrase(Secret::new(password.expose_secret().clone()));
+    let encryptor = match encryption {
+        Encryption::Passphrase(password) => {
+            age::Encryptor::with_user_passphrase(Secret::new(password.expose_secret().clone()))
+        }
+        Encryption::Recipients(recipients) => age::Encryptor::with_recipients(
+            recipients
+                .iter()
+                .map(|r| Box::new(r.clone()) as Box<dyn age::Recipient + Send>)
+                .collect(),
+        )
+        .ok_or("no recipients given")?,
+    };
     let mut age_writer = encryptor.wrap_output(output_writer)?;
-    println!("Encryption Header (Scrypt): {:?}", start_enc.elapsed());
+    eprintln!("Encryption Header: {:?}", start_enc.elapsed());
 
-    let _start_comp = std::time::Instant::now();
-    
-    let mut params = BrotliEncoderParams::default();
-    params.quality = quality as i32;
-    
-    {
-        let mut compressor = brotli::CompressorWriter::with_params(&mut age_writer, 65536, &params);
-        io::copy(&mut input_reader, &mut compressor)?;
-        compressor.flush()?;
-    } // Compressor dropped here
+    let start_comp = std::time::Instant::now();
+    compress(input_reader, &mut age_writer, compression)?;
+    eprintln!("Compression ({}): {:?}", compression.codec.label(), start_comp.elapsed());
     
     age_writer.finish()?;
     
-    println!("Total Encrypt Time: {:?}", start_total.elapsed());
+    eprintln!("Total Encrypt Time: {:?}", start_total.elapsed());
 
     Ok(())
 }
 
-pub fn decrypt_stream(
+/// Reader yielding the decompressed contents of `input`, by the codec its
+/// first bytes name; plain SQLite passes through unchanged.
+fn decompressing_reader<'a>(mut input: impl io::BufRead + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    let head = input.fill_buf()?;
+    Ok(if head.starts_with(SQLITE_MAGIC) {
+        Box::new(input)
+    } else {
+        match Codec::from_magic(head) {
+            Some(Codec::Zstd) => Box::new(zstd::stream::read::Decoder::with_buffer(input)?),
+            // Multi-member, like `gzip -d`, so concatenated files read whole
+            Some(Codec::Gzip) => Box::new(flate2::read::MultiGzDecoder::new(input)),
+            // Chunked output of `encrypt_stream` is joined into a single stream, so one decompressor reads it all
+            _ => Box::new(brotli::Decompressor::new(input, BUFFER_SIZE)),
+        }
+    })
+}
+
+/// Why decoding a file failed. A wrong passphrase can be retried; the other
+/// errors mean the file, the keys or the disk need attention.
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("wrong passphrase")]
+    WrongPassphrase,
+
+    #[error("none of the identities can decrypt this file")]
+    NoMatchingIdentity,
+
+    /// Credentials missing or of the wrong kind for the file
+    #[error("{0}")]
+    Credentials(&'static str),
+
+    /// The data does not decode: damaged, truncated or not what its header says
+    #[error("the file is corrupt or truncated ({0})")]
+    Corrupt(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Age(age::DecryptError),
+}
+
+impl DecodeError {
+    /// What the user can do about the error, where that is not obvious from the message.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            DecodeError::Corrupt(_) => Some("The file is damaged or incomplete; download or copy it again."),
+            DecodeError::Io(_) => {
+                Some("Check that the file is readable and that the temporary directory has free space (or use --in-memory).")
+            }
+            DecodeError::NoMatchingIdentity => Some("Pass the key file the database was encrypted for with --identity."),
+            _ => None,
+        }
+    }
+
+    /// Classify an error from reading decoded data: errors from the operating
+    /// system are I/O errors, errors raised by a decoder mean corrupt data.
+    fn read(e: io::Error) -> Self {
+        if e.raw_os_error().is_some() { DecodeError::Io(e) } else { DecodeError::Corrupt(e.to_string()) }
+    }
+}
+
+impl From<age::DecryptError> for DecodeError {
+    fn from(e: age::DecryptError) -> Self {
+        match e {
+            age::DecryptError::NoMatchingKeys => DecodeError::NoMatchingIdentity,
+            age::DecryptError::Io(e) => DecodeError::read(e),
+            age::DecryptError::DecryptionFailed
+            | age::DecryptError::InvalidHeader
+            | age::DecryptError::InvalidMac
+            | age::DecryptError::KeyDecryptionFailed
+            | age::DecryptError::UnknownFormat => DecodeError::Corrupt(e.to_string()),
+            e => DecodeError::Age(e),
+        }
+    }
+}
+
+/// Stage of decoding, as reported to a progress callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Deriving the key from the passphrase (scrypt); no input is read meanwhile
+    KeyDerivation,
+    /// Decrypting (and decompressing) an age file
+    Decrypt,
+    /// Decompressing a file that is not encrypted
+    Decompress,
+}
+
+impl Phase {
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::KeyDerivation => "Deriving key",
+            Phase::Decrypt => "Decrypting",
+            Phase::Decompress => "Decompressing",
+        }
+    }
+}
+
+/// How far decoding has got: input bytes read out of the file size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub phase: Phase,
+    pub read: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// Fraction of the input read, 0.0 to 1.0.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 { 1.0 } else { (self.read as f64 / self.total as f64).min(1.0) }
+    }
+}
+
+/// Reader counting the bytes that pass through it into a shared cell.
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// A reader of decoded data with what is needed to report its progress.
+struct Decoding {
+    reader: Box<dyn Read>,
+    phase: Phase,
+    /// Input bytes read so far
+    count: Rc<Cell<u64>>,
+}
+
+/// Reader yielding the database inside `input_path`: decrypted with
+/// `credentials` if it is an age file, then decompressed.
+fn decoding_reader(
     input_path: &Path,
-    output_path: &Path,
-    password: Secret<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let start_total = std::time::Instant::now();
-    const BUFFER_SIZE: usize = 65536;
+    credentials: Option<&Credentials>,
+    progress: &mut dyn FnMut(Progress),
+) -> Result<Decoding, DecodeError> {
     let input_file = File::open(input_path)?;
+    let total = input_file.metadata()?.len();
+    let count = Rc::new(Cell::new(0));
+    let counted = CountingReader { inner: input_file, count: Rc::clone(&count) };
     // Use BufReader for input to reduce syscalls during decryption
-    let input_reader = io::BufReader::with_capacity(BUFFER_SIZE, input_file);
-    
+    let mut input_reader = io::BufReader::with_capacity(BUFFER_SIZE, counted);
+    if !io::BufRead::fill_buf(&mut input_reader)?.starts_with(AGE_MAGIC) {
+        let reader = decompressing_reader(input_reader).map_err(DecodeError::read)?;
+        return Ok(Decoding { reader, phase: Phase::Decompress, count });
+    }
+    let credentials = credentials.ok_or(DecodeError::Credentials("file is encrypted; a passphrase or identity is needed"))?;
+
+    let reader = match (age::Decryptor::new(input_reader)?, credentials) {
+        (age::Decryptor::Passphrase(d), Credentials::Passphrase(password)) => {
+            progress(Progress { phase: Phase::KeyDerivation, read: count.get(), total });
+            let max_work_factor = if cfg!(debug_assertions) { Some(22) } else { None };
+            // The scrypt stanza fails to open exactly when the passphrase is wrong
+            d.decrypt(password, max_work_factor).map_err(|e| match e {
+                age::DecryptError::DecryptionFailed => DecodeError::WrongPassphrase,
+                e => e.into(),
+            })?
+        }
+        (age::Decryptor::Recipients(d), Credentials::Identities(identities)) => {
+            d.decrypt(identities.iter().map(|i| i as &dyn age::Identity))?
+        }
+        (age::Decryptor::Passphrase(_), Credentials::Identities(_)) => {
+            return Err(DecodeError::Credentials("file is encrypted with a passphrase, not to recipients"));
+        }
+        (age::Decryptor::Recipients(_), Credentials::Passphrase(_)) => {
+            return Err(DecodeError::Credentials("file is encrypted to recipients; an identity file (--identity) is needed"));
+        }
+    };
+
+    let reader = decompressing_reader(io::BufReader::with_capacity(BUFFER_SIZE, reader)).map_err(DecodeError::read)?;
+    Ok(Decoding { reader, phase: Phase::Decrypt, count })
+}
+
+/// Decode `input_path` into `output`, calling `progress` after each buffer.
+fn decode_into(
+    input_path: &Path,
+    output: &mut impl Write,
+    credentials: Option<&Credentials>,
+    progress: &mut dyn FnMut(Progress),
+) -> Result<(), DecodeError> {
+    let total = std::fs::metadata(input_path)?.len();
+    let Decoding { mut reader, phase, count } = decoding_reader(input_path, credentials, progress)?;
+    progress(Progress { phase, read: count.get(), total });
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(DecodeError::read(e)),
+        };
+        output.write_all(&buffer[..n])?;
+        progress(Progress { phase, read: count.get(), total });
+    }
+    Ok(())
+}
+
+/// Write the database inside `input_path` (age, compressed or both) to `output_path`.
+///
+/// `credentials` are only needed for age files. `progress` is called as the
+/// input is read; nothing is printed, so this is safe to run under the TUI.
+pub fn decode_stream(
+    input_path: &Path,
+    output_path: &Path,
+    credentials: Option<&Credentials>,
+    progress: &mut dyn FnMut(Progress),
+) -> Result<(), DecodeError> {
     let output_file = File::create(output_path)?;
     // Wrap output in BufWriter
     let mut output_writer = io::BufWriter::with_capacity(BUFFER_SIZE, output_file);
+    decode_into(input_path, &mut output_writer, credentials, progress)?;
+    output_writer.flush()?;
+    Ok(())
+}
 
-    let start_dec = std::time::Instant::now();
-    let decryptor = match age::Decryptor::new(input_reader)? {
-        age::Decryptor::Passphrase(d) => d,
-        _ => return Err("Input file is not encrypted with a passphrase".into()),
-    };
+/// Like `decode_stream`, but into memory, never touching disk.
+pub fn decode_to_memory(
+    input_path: &Path,
+    credentials: Option<&Credentials>,
+    progress: &mut dyn FnMut(Progress),
+) -> Result<Vec<u8>, DecodeError> {
+    let mut plain = Vec::new();
+    decode_into(input_path, &mut plain, credentials, progress)?;
+    Ok(plain)
+}
 
-    let max_work_factor = if cfg!(debug_assertions) { Some(22) } else { None };
-    let reader = decryptor.decrypt(&password, max_work_factor)?;
-    println!("Decryption Init: {:?}", start_dec.elapsed());
-    
-    let _start_decomp = std::time::Instant::now();
-    
-    // Use brotli::Decompressor which handles concatenated streams automatically
-    let mut decompressor = brotli::Decompressor::new(reader, BUFFER_SIZE);
-    io::copy(&mut decompressor, &mut output_writer)?;
-    
-    output_writer.flush()?;
-    println!("Total Decrypt Time: {:?}", start_total.elapsed());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn test_compress_parallel_joins_chunks() {
+        let text: Vec<u8> = (0..20_000u32).flat_map(|i| format!("row {} {}\n", i, i % 7).into_bytes()).collect();
+        for input in [&text[..], &text[..4000], &[][..]] {
+            let compression = Compression { codec: Codec::Brotli, quality: 5, chunk_size: 1000, threads: 3 };
+            let mut compressed = Vec::new();
+            compress_parallel(input, &mut compressed, &compression).unwrap();
+            let mut decoded = Vec::new();
+            brotli::Decompressor::new(&compressed[..], 4096).read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, input, "{} bytes", input.len());
+        }
+    }
+
+    #[test]
+    fn test_recipients_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = age::x25519::Identity::generate();
+        let id_file = dir.path().join("key.txt");
+        std::fs::write(&id_file, format!("# created: now\n{}\n", identity.to_string().expose_secret())).unwrap();
+        let team = dir.path().join("team.txt");
+        std::fs::write(&team, format!("# team\n\n{}\n", identity.to_public())).unwrap();
+
+        let recipients = parse_recipients(&[], &[team]).unwrap();
+        assert_eq!(recipients.len(), 1);
+        assert!(parse_recipients(&["age1bogus".into()], &[]).is_err());
+
+        let plain = dir.path().join("plain.db");
+        let encrypted = dir.path().join("plain.db.age");
+        std::fs::write(&plain, b"SQLite format 3\0 and some rows").unwrap();
+        // zstd inside age is found after decrypting
+        let compression = Compression { codec: Codec::Zstd, ..Compression::new(1) };
+        encrypt_stream(&plain, &encrypted, &Encryption::Recipients(recipients), &compression).unwrap();
+        assert!(matches!(detect(&encrypted).unwrap(), FileKind::AgeRecipients { stanzas } if stanzas == ["X25519"]));
+
+        let credentials = Credentials::Identities(read_identities(&[id_file]).unwrap());
+        let mut phases = Vec::new();
+        let decoded = decode_to_memory(&encrypted, Some(&credentials), &mut |p| phases.push(p.phase)).unwrap();
+        assert_eq!(decoded, std::fs::read(&plain).unwrap());
+        // No key derivation for X25519
+        assert!(!phases.is_empty() && phases.iter().all(|p| *p == Phase::Decrypt));
+        let stranger = Credentials::Identities(vec![age::x25519::Identity::generate()]);
+        assert!(matches!(
+            decode_to_memory(&encrypted, Some(&stranger), &mut |_| {}),
+            Err(DecodeError::NoMatchingIdentity)
+        ));
+        let password = Credentials::Passphrase(Secret::new("pw".to_string()));
+        assert!(decode_to_memory(&encrypted, Some(&password), &mut |_| {}).unwrap_err().to_string().contains("--identity"));
+        assert!(decode_to_memory(&encrypted, None, &mut |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_detect_and_decode_compressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain: Vec<u8> = SQLITE_MAGIC.iter().copied().chain((0..5000u32).flat_map(|i| i.to_le_bytes())).collect();
+        let path = dir.path().join("plain.db");
+        std::fs::write(&path, &plain).unwrap();
+        assert_eq!(detect(&path).unwrap(), FileKind::Sqlite);
+        assert_eq!(decode_to_memory(&path, None, &mut |_| {}).unwrap(), plain);
+
+        for codec in [Codec::Brotli, Codec::Zstd, Codec::Gzip] {
+            let compression = Compression { codec, quality: codec.qualities()[0], chunk_size: 4096, threads: 2 };
+            let mut compressed = Vec::new();
+            compress(&plain[..], &mut compressed, &compression).unwrap();
+            let path = dir.path().join(codec.label());
+            std::fs::write(&path, &compressed).unwrap();
+            assert_eq!(detect(&path).unwrap(), FileKind::Compressed(codec));
+            let mut reports = Vec::new();
+            assert_eq!(decode_to_memory(&path, None, &mut |p| reports.push(p)).unwrap(), plain, "{}", codec.label());
+            let total = compressed.len() as u64;
+            assert!(reports.iter().all(|p| p.phase == Phase::Decompress && p.total == total));
+            assert!(reports.windows(2).all(|w| w[0].read <= w[1].read));
+            assert_eq!(reports.last().unwrap().ratio(), 1.0);
+        }
+
+        let other = dir.path().join("notes.txt");
+        std::fs::write(&other, b"just some text").unwrap();
+        assert_eq!(detect(&other).unwrap(), FileKind::Unknown);
+    }
+
+    #[test]
+    fn test_decode_errors_are_classified() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.db");
+        std::fs::write(&plain, SQLITE_MAGIC.repeat(4096)).unwrap();
+
+        let encrypted = dir.path().join("plain.db.age");
+        let password = |p: &str| Credentials::Passphrase(Secret::new(p.to_string()));
+        encrypt_stream(&plain, &encrypted, &Encryption::Passphrase(Secret::new("right".to_string())), &Compression::new(1))
+            .unwrap();
+        assert!(matches!(
+            decode_to_memory(&encrypted, Some(&password("wrong")), &mut |_| {}),
+            Err(DecodeError::WrongPassphrase)
+        ));
+
+        // Damage after the header is corruption, not a wrong key
+        let identity = age::x25519::Identity::generate();
+        let to_identity = dir.path().join("identity.age");
+        encrypt_stream(&plain, &to_identity, &Encryption::Recipients(vec![identity.to_public()]), &Compression::new(1))
+            .unwrap();
+        let mut bytes = std::fs::read(&to_identity).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        std::fs::write(&to_identity, &bytes).unwrap();
+        let credentials = Credentials::Identities(vec![identity]);
+        let error = decode_to_memory(&to_identity, Some(&credentials), &mut |_| {}).unwrap_err();
+        assert!(matches!(error, DecodeError::Corrupt(_)), "{:?}", error);
+        assert!(error.hint().is_some());
+
+        let truncated = dir.path().join("truncated.zst");
+        let mut compressed = Vec::new();
+        compress(&std::fs::read(&plain).unwrap()[..], &mut compressed, &Compression { codec: Codec::Zstd, ..Compression::new(1) })
+            .unwrap();
+        std::fs::write(&truncated, &compressed[..compressed.len() / 2]).unwrap();
+        assert!(matches!(decode_to_memory(&truncated, None, &mut |_| {}), Err(DecodeError::Corrupt(_))));
+
+        assert!(matches!(
+            decode_to_memory(&dir.path().join("missing.age"), None, &mut |_| {}),
+            Err(DecodeError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_age_header() {
+        let passphrase = b"age-encryption.org/v1\n-> scrypt c2FsdA 18\nYm9keQ\n--- bWFj\n\x00binary";
+        assert_eq!(parse_age_header(passphrase), FileKind::AgePassphrase { work_factor: 18 });
+
+        let recipients = b"age-encryption.org/v1\n-> X25519 a2V5\nYm9keQ\n-> 9-grease x\n\n-> ssh-ed25519 dGFn a2V5\nYm9keQ\n--- bWFj\n";
+        assert_eq!(
+            parse_age_header(recipients),
+            FileKind::AgeRecipients { stanzas: vec!["X25519".into(), "ssh-ed25519".into()] }
+        );
+    }
 }
@@ -1,3 +1,7 @@
+use crate::fulltext::{FulltextHit, FulltextIndex};
+use crate::schema::{self, Schema, SchemaError};
+use crate::sqlite_file;
+use crate::vector::{self, Metric};
 use std::path::Path;
 use turso::Value;
 
@@ -34,10 +38,73 @@ pub struct TranscriptListItem {
     pub has_embedding: bool,
     pub model: String,
     pub original_source_link: String,
+    pub output_language: String,
     pub summary_input_tokens: i64,
     pub summary_output_tokens: i64,
     pub summary_timestamp_start: String,
     pub summary_timestamp_end: String,
+    // User annotations, merged in from the sidecar store after loading.
+    pub bookmarked: bool,
+    pub tags: Vec<String>,
+}
+
+#[cfg(test)]
+impl TranscriptListItem {
+    /// Test item with the given identifier and empty fields otherwise;
+    /// tests set what they need with struct update syntax.
+    pub fn fixture(identifier: i64) -> Self {
+        Self {
+            identifier,
+            host: String::new(),
+            summary: String::new(),
+            cost: 0.0,
+            has_embedding: false,
+            model: String::new(),
+            original_source_link: String::new(),
+            output_language: String::new(),
+            summary_input_tokens: 0,
+            summary_output_tokens: 0,
+            summary_timestamp_start: String::new(),
+            summary_timestamp_end: String::new(),
+            bookmarked: false,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Default embedding prefix length used for similarity. Matryoshka embeddings
+/// of different sizes stay comparable when truncated to the same prefix.
+pub const SIMILARITY_DIMS: usize = 768;
+
+/// Rows between progress reports while building the full-text index.
+const FULLTEXT_PROGRESS_STEP: usize = 200;
+
+/// Parameters of a similarity search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityQuery {
+    pub metric: Metric,
+    /// Embedding prefix length; `None` compares full embeddings, and then
+    /// only against rows whose embedding has the same length.
+    pub dims: Option<usize>,
+    /// Drop results farther away than this; see `Metric::valid_cutoff`.
+    pub max_distance: Option<f64>,
+    pub limit: i64,
+    pub offset: i64,
+    /// Only rank these identifiers (candidates proposed by the ANN index).
+    pub candidates: Option<Vec<i64>>,
+}
+
+impl Default for SimilarityQuery {
+    fn default() -> Self {
+        Self {
+            metric: Metric::Cosine,
+            dims: Some(SIMILARITY_DIMS),
+            max_distance: None,
+            limit: 20,
+            offset: 0,
+            candidates: None,
+        }
+    }
 }
 
 /// Result of a vector similarity search.
@@ -87,32 +154,228 @@ fn val_bool(v: &Value) -> bool {
     }
 }
 
+/// Comma-separated identifier list for an `IN (...)` clause.
+fn id_list(ids: &[i64]) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Errors from opening a database file.
+#[derive(thiserror::Error, Debug)]
+pub enum OpenError {
+    #[error("{0}")]
+    Turso(#[from] turso::Error),
+
+    #[error("Incompatible database schema: {0}")]
+    Schema(#[from] SchemaError),
+
+    #[error("Invalid database image: {0}")]
+    Image(String),
+}
+
 /// Database handle wrapping a turso connection.
 pub struct Database {
+    db: turso::Database,
     conn: turso::Connection,
+    schema: Schema,
+    /// Inverted index for full-text search, built on first use.
+    fulltext: tokio::sync::OnceCell<FulltextIndex>,
+    /// Loaded from a decrypted image; nothing derived from it may be written to disk.
+    ephemeral: bool,
+    /// Tables of the image that could not be loaded (`WITHOUT ROWID`).
+    skipped_tables: Vec<String>,
 }
 
 impl Database {
-    /// Open a local SQLite database file via Turso.
-    pub async fn open(path: &Path) -> turso::Result<Self> {
+    /// Open a local SQLite database file via Turso and check its schema.
+    pub async fn open(path: &Path) -> Result<Self, OpenError> {
         let path_str = path.to_string_lossy().to_string();
         let db = turso::Builder::new_local(&path_str).build().await?;
+        Self::from_turso(db, false).await
+    }
+
+    /// Load a database image held in memory (a decrypted export) into an
+    /// in-memory database, so no plaintext copy is written to disk.
+    ///
+    /// Tables are recreated from their `CREATE` statements and refilled row by
+    /// row, then the indexes are built; the image can be dropped afterwards.
+    /// `WITHOUT ROWID` tables are left out (see `skipped_tables`).
+    pub async fn open_image(image: &[u8]) -> Result<Self, OpenError> {
+        let db = turso::Builder::new_local(":memory:").build().await?;
+        let conn = db.connect()?;
+        let entries = sqlite_file::schema(image).map_err(OpenError::Image)?;
+        let mut skipped_tables = Vec::new();
+        conn.execute("BEGIN", ()).await?;
+        for entry in entries.iter().filter(|e| e.kind == "table" && e.root_page != 0) {
+            if entry.name.starts_with("sqlite_") {
+                continue;
+            }
+            // Neither the image reader nor Turso handles these; the transcripts never live in one
+            if entry.is_without_rowid() {
+                skipped_tables.push(entry.name.clone());
+                continue;
+            }
+            conn.execute(&entry.sql, ()).await?;
+            let quoted = format!("\"{}\"", entry.name.replace('"', "\"\""));
+
+            // Column names, and the rowid alias whose value is only stored as the rowid
+            let mut columns = Vec::new();
+            let mut keys = Vec::new();
+            let mut rows = conn.query(format!("PRAGMA table_info({})", quoted), ()).await?;
+            while let Some(row) = rows.next().await? {
+                let name = val_string(&row.get_value(1)?);
+                if val_i64(&row.get_value(5)?) > 0 {
+                    keys.push((columns.len(), val_string(&row.get_value(2)?)));
+                }
+                columns.push(format!("\"{}\"", name.replace('"', "\"\"")));
+            }
+            drop(rows);
+            let alias = match &keys[..] {
+                [(i, kind)] if kind.eq_ignore_ascii_case("INTEGER") => Some(*i),
+                _ => None,
+            };
+
+            // One statement per record length, as older rows may lack added columns
+            let mut inserts: Vec<Option<String>> = vec![None; columns.len() + 1];
+            for row in sqlite_file::rows(image, entry.root_page).map_err(OpenError::Image)? {
+                let (rowid, mut values) = row.map_err(OpenError::Image)?;
+                values.truncate(columns.len());
+                if let Some(i) = alias
+                    && values.get(i) == Some(&Value::Null)
+                {
+                    values[i] = Value::Integer(rowid);
+                }
+                let sql = inserts[values.len()].get_or_insert_with(|| {
+                    if values.is_empty() {
+                        // As `DEFAULT VALUES`, but keeping the rowid
+                        return format!("INSERT INTO {} (rowid) VALUES (?1)", quoted);
+                    }
+                    let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+                    format!(
+                        "INSERT INTO {} ({}) VALUES ({})",
+                        quoted,
+                        columns[..values.len()].join(", "),
+                        placeholders.join(", ")
+                    )
+                });
+                if values.is_empty() {
+                    values.push(Value::Integer(rowid));
+                }
+                conn.execute(sql.as_str(), turso::params::Params::Positional(values)).await?;
+            }
+        }
+        for entry in entries.iter().filter(|e| e.kind == "index" && !e.sql.is_empty()) {
+            if !skipped_tables.contains(&entry.table) {
+                conn.execute(&entry.sql, ()).await?;
+            }
+        }
+        conn.execute("COMMIT", ()).await?;
+        let mut database = Self::from_turso(db, true).await?;
+        database.skipped_tables = skipped_tables;
+        Ok(database)
+    }
+
+    async fn from_turso(db: turso::Database, ephemeral: bool) -> Result<Self, OpenError> {
         let conn = db.connect()?;
-        Ok(Database { conn })
+        let schema = Self::introspect(&conn).await?;
+        Ok(Database {
+            db,
+            conn,
+            schema,
+            fulltext: tokio::sync::OnceCell::new(),
+            ephemeral,
+            skipped_tables: Vec::new(),
+        })
+    }
+
+    /// Find the transcript table and map its columns onto the ones the queries expect.
+    async fn introspect(conn: &turso::Connection) -> Result<Schema, OpenError> {
+        let mut tables = Vec::new();
+        let mut rows = conn
+            .query("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name", ())
+            .await?;
+        while let Some(row) = rows.next().await? {
+            tables.push(val_string(&row.get_value(0)?));
+        }
+        let table = Schema::find_table(&tables)?;
+
+        let mut columns = Vec::new();
+        let mut rows = conn
+            .query(format!("PRAGMA table_info(\"{}\")", table), ())
+            .await?;
+        while let Some(row) = rows.next().await? {
+            columns.push(val_string(&row.get_value(1)?));
+        }
+        Ok(Schema::resolve(&table, &columns)?)
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Tables of a decoded image that `open_image` could not load.
+    pub fn skipped_tables(&self) -> &[String] {
+        &self.skipped_tables
+    }
+
+    /// Schema warnings, plus the tables that were not loaded.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut notes = self.schema.warnings();
+        if !self.skipped_tables.is_empty() {
+            notes.push(format!("WITHOUT ROWID table(s) not loaded: {}", self.skipped_tables.join(", ")));
+        }
+        notes
+    }
+
+    /// True for databases decoded in memory with `open_image`, whose data
+    /// (including caches derived from it) must stay off the disk.
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    /// The underlying connection, for maintenance commands that modify the table.
+    pub fn connection(&self) -> &turso::Connection {
+        &self.conn
+    }
+
+    /// Open a second handle on the same database with its own connection,
+    /// so background tasks do not interleave statements with the UI.
+    pub fn reader(&self) -> turso::Result<Self> {
+        Ok(Database {
+            db: self.db.clone(),
+            conn: self.db.connect()?,
+            schema: self.schema.clone(),
+            fulltext: tokio::sync::OnceCell::new(),
+            ephemeral: self.ephemeral,
+            skipped_tables: self.skipped_tables.clone(),
+        })
     }
 
     /// Load all transcripts metadata for in-memory caching.
     pub async fn list_all_transcripts(&self) -> turso::Result<Vec<TranscriptListItem>> {
         let mut items = Vec::new();
-        let mut rows = self.conn.query(
-            "SELECT identifier, host, COALESCE(summary, ''), cost, \
-             CASE WHEN embedding IS NOT NULL THEN 1 ELSE 0 END, model, \
-             COALESCE(original_source_link, ''), \
-             COALESCE(summary_input_tokens, 0), COALESCE(summary_output_tokens, 0), \
-             COALESCE(summary_timestamp_start, ''), COALESCE(summary_timestamp_end, '') \
-             FROM items ORDER BY identifier",
-            (),
-        ).await?;
+        let c = |name| self.schema.col(name);
+        let sql = format!(
+            "SELECT {identifier}, {host}, COALESCE({summary}, ''), {cost}, \
+             CASE WHEN {embedding} IS NOT NULL THEN 1 ELSE 0 END, {model}, \
+             COALESCE({link}, ''), \
+             COALESCE({input_tokens}, 0), COALESCE({output_tokens}, 0), \
+             COALESCE({ts_start}, ''), COALESCE({ts_end}, ''), COALESCE({language}, '') \
+             FROM {table} ORDER BY {identifier}",
+            identifier = c("identifier"),
+            host = c("host"),
+            summary = c("summary"),
+            cost = c("cost"),
+            embedding = c("embedding"),
+            model = c("model"),
+            link = c("original_source_link"),
+            input_tokens = c("summary_input_tokens"),
+            output_tokens = c("summary_output_tokens"),
+            ts_start = c("summary_timestamp_start"),
+            ts_end = c("summary_timestamp_end"),
+            language = c("output_language"),
+            table = self.schema.table_sql(),
+        );
+        let mut rows = self.conn.query(sql, ()).await?;
 
         while let Some(row) = rows.next().await? {
             items.push(TranscriptListItem {
@@ -127,6 +390,9 @@ impl Database {
                 summary_output_tokens: val_i64(&row.get_value(8)?),
                 summary_timestamp_start: val_string(&row.get_value(9)?),
                 summary_timestamp_end: val_string(&row.get_value(10)?),
+                output_language: val_string(&row.get_value(11)?),
+                bookmarked: false,
+                tags: Vec::new(),
             });
         }
         Ok(items)
@@ -134,22 +400,41 @@ impl Database {
 
     /// Get a single transcript by identifier.
     pub async fn get_transcript(&self, id: i64) -> turso::Result<Option<TranscriptRow>> {
+        let c = |name| self.schema.col(name);
+        let sql = format!(
+            "SELECT {identifier}, {model}, {host}, \
+             COALESCE({link}, ''), COALESCE({language}, ''), \
+             COALESCE({summary}, ''), {summary_done}, \
+             COALESCE({input_tokens}, 0), COALESCE({output_tokens}, 0), \
+             COALESCE({transcript}, ''), COALESCE({timestamps}, ''), \
+             COALESCE({youtube}, ''), \
+             COALESCE({cost}, 0), \
+             CASE WHEN {embedding} IS NOT NULL THEN 1 ELSE 0 END, \
+             COALESCE({embedding_model}, ''), \
+             COALESCE({ts_start}, ''), COALESCE({ts_end}, '') \
+             FROM {table} WHERE {identifier} = ?1",
+            identifier = c("identifier"),
+            model = c("model"),
+            host = c("host"),
+            link = c("original_source_link"),
+            language = c("output_language"),
+            summary = c("summary"),
+            summary_done = c("summary_done"),
+            input_tokens = c("summary_input_tokens"),
+            output_tokens = c("summary_output_tokens"),
+            transcript = c("transcript"),
+            timestamps = c("timestamps"),
+            youtube = c("timestamped_summary_in_youtube_format"),
+            cost = c("cost"),
+            embedding = c("embedding"),
+            embedding_model = c("embedding_model"),
+            ts_start = c("summary_timestamp_start"),
+            ts_end = c("summary_timestamp_end"),
+            table = self.schema.table_sql(),
+        );
         let mut rows = self
             .conn
-            .query(
-                "SELECT identifier, model, host, \
-                 COALESCE(original_source_link, ''), COALESCE(output_language, ''), \
-                 COALESCE(summary, ''), summary_done, \
-                 COALESCE(summary_input_tokens, 0), COALESCE(summary_output_tokens, 0), \
-                 COALESCE(transcript, ''), COALESCE(timestamps, ''), \
-                 COALESCE(timestamped_summary_in_youtube_format, ''), \
-                 COALESCE(cost, 0), \
-                 CASE WHEN embedding IS NOT NULL THEN 1 ELSE 0 END, \
-                 COALESCE(embedding_model, ''), \
-                 COALESCE(summary_timestamp_start, ''), COALESCE(summary_timestamp_end, '') \
-                 FROM items WHERE identifier = ?1",
-                turso::params::Params::Positional(vec![Value::Integer(id)]),
-            )
+            .query(sql, turso::params::Params::Positional(vec![Value::Integer(id)]))
             .await?;
 
         if let Some(row) = rows.next().await? {
@@ -177,36 +462,217 @@ impl Database {
         }
     }
 
-    /// Find transcripts similar to the given one using cosine distance.
-    /// Uses vector_slice(..., 0, 768) to handle Matryoshka dimension mismatch.
+    /// Find transcripts similar to the given one.
+    /// By default both sides are sliced to 768 dimensions to handle Matryoshka size mismatch.
     pub async fn find_similar(
         &self,
         source_id: i64,
-        limit: i64,
+        query: &SimilarityQuery,
     ) -> turso::Result<Vec<SimilarResult>> {
-        let mut results = Vec::new();
+        if !self.schema.has_embeddings() {
+            return Ok(Vec::new());
+        }
 
-        // Slice both source and target embeddings to 768 dimensions for comparison.
-        // This allows 3072-dim and 768-dim embeddings to be compared.
-        let mut rows = self
+        let sql = self.similar_sql(
+            query,
+            "s.embedding",
+            &format!(
+                ", (SELECT {embedding} AS embedding FROM {table} WHERE {identifier} = ?1) s",
+                embedding = self.schema.col("embedding"),
+                identifier = self.schema.col("identifier"),
+                table = self.schema.table_sql(),
+            ),
+            &format!("{} != ?1", self.schema.col_of("t", "identifier")),
+        );
+        let rows = self
+            .conn
+            .query(sql, Self::similar_params(Value::Integer(source_id), query))
+            .await?;
+        Self::collect_similar(rows).await
+    }
+
+    /// Find transcripts closest to the centroid of several seed transcripts.
+    ///
+    /// Seeds without an embedding are ignored and all seeds are excluded from
+    /// the results. Returns the results and the number of seeds that contributed.
+    pub async fn find_similar_to_centroid(
+        &self,
+        seed_ids: &[i64],
+        query: &SimilarityQuery,
+    ) -> turso::Result<(Vec<SimilarResult>, usize)> {
+        let embeddings = self
+            .get_embeddings(seed_ids, query.dims.unwrap_or(usize::MAX))
+            .await?;
+        let vectors: Vec<Vec<f32>> = embeddings.into_iter().map(|(_, v)| v).collect();
+        let Some(centroid) = vector::centroid(&vectors) else {
+            return Ok((Vec::new(), 0));
+        };
+        let results = self.find_similar_to_vector(&centroid, seed_ids, query).await?;
+        Ok((results, vectors.len()))
+    }
+
+    /// Find transcripts closest to an arbitrary query vector, skipping `exclude`.
+    pub async fn find_similar_to_vector(
+        &self,
+        vector: &[f32],
+        exclude: &[i64],
+        query: &SimilarityQuery,
+    ) -> turso::Result<Vec<SimilarResult>> {
+        if !self.schema.has_embeddings() || vector.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let exclude_sql = if exclude.is_empty() {
+            "1".to_string()
+        } else {
+            format!("{} NOT IN ({})", self.schema.col_of("t", "identifier"), id_list(exclude))
+        };
+        let sql = self.similar_sql(query, "?1", "", &exclude_sql);
+        let rows = self
             .conn
             .query(
-                "SELECT t.identifier, t.host, COALESCE(t.summary, ''), \
-                 vector_distance_cos(vector_slice(t.embedding, 0, 768), vector_slice(s.embedding, 0, 768)) AS dist, \
-                 COALESCE(t.original_source_link, ''), t.model, t.cost, \
-                 COALESCE(t.summary_input_tokens, 0), COALESCE(t.summary_output_tokens, 0), \
-                 COALESCE(t.summary_timestamp_start, ''), COALESCE(t.summary_timestamp_end, '') \
-                 FROM items t, (SELECT embedding FROM items WHERE identifier = ?1) s \
-                 WHERE t.embedding IS NOT NULL AND t.identifier != ?1 \
-                 ORDER BY dist \
-                 LIMIT ?2",
-                turso::params::Params::Positional(vec![
-                    Value::Integer(source_id),
-                    Value::Integer(limit),
-                ]),
+                sql,
+                Self::similar_params(Value::Blob(vector::encode_embedding(vector)), query),
             )
             .await?;
+        Self::collect_similar(rows).await
+    }
+
+    /// Fetch the embeddings of the given transcripts, truncated to `dims` values.
+    /// Transcripts without an embedding are left out.
+    pub async fn get_embeddings(&self, ids: &[i64], dims: usize) -> turso::Result<Vec<(i64, Vec<f32>)>> {
+        let mut out = Vec::new();
+        if !self.schema.has_embeddings() || ids.is_empty() {
+            return Ok(out);
+        }
+        let sql = format!(
+            "SELECT {identifier}, {embedding} FROM {table} \
+             WHERE {embedding} IS NOT NULL AND {identifier} IN ({ids})",
+            identifier = self.schema.col("identifier"),
+            embedding = self.schema.col("embedding"),
+            table = self.schema.table_sql(),
+            ids = id_list(ids),
+        );
+        let mut rows = self.conn.query(sql, ()).await?;
+        while let Some(row) = rows.next().await? {
+            if let Value::Blob(blob) = row.get_value(1)? {
+                out.push((val_i64(&row.get_value(0)?), vector::decode_embedding(&blob, dims)));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Fetch every embedding, truncated to `dims` values, in identifier order.
+    pub async fn all_embeddings(&self, dims: usize) -> turso::Result<Vec<(i64, Vec<f32>)>> {
+        let mut out = Vec::new();
+        if !self.schema.has_embeddings() {
+            return Ok(out);
+        }
+        let sql = format!(
+            "SELECT {identifier}, {embedding} FROM {table} WHERE {embedding} IS NOT NULL ORDER BY {identifier}",
+            identifier = self.schema.col("identifier"),
+            embedding = self.schema.col("embedding"),
+            table = self.schema.table_sql(),
+        );
+        let mut rows = self.conn.query(sql, ()).await?;
+        while let Some(row) = rows.next().await? {
+            if let Value::Blob(blob) = row.get_value(1)? {
+                out.push((val_i64(&row.get_value(0)?), vector::decode_embedding(&blob, dims)));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Summary of the embedding data, used to key cached indexes: the row
+    /// count and a hash over every identifier and embedding BLOB, so editing
+    /// an embedding in place invalidates the cache.
+    pub async fn embedding_fingerprint(&self) -> turso::Result<String> {
+        use std::hash::Hasher;
+        let sql = format!(
+            "SELECT {identifier}, {embedding} FROM {table} WHERE {embedding} IS NOT NULL ORDER BY {identifier}",
+            identifier = self.schema.col("identifier"),
+            embedding = self.schema.col("embedding"),
+            table = self.schema.table_sql(),
+        );
+        let mut rows = self.conn.query(sql, ()).await?;
+        // Not stable across Rust releases; a toolchain update only costs a rebuild.
+        let mut hasher = std::hash::DefaultHasher::new();
+        let mut count = 0u64;
+        while let Some(row) = rows.next().await? {
+            hasher.write_i64(val_i64(&row.get_value(0)?));
+            if let Value::Blob(blob) = row.get_value(1)? {
+                hasher.write_usize(blob.len());
+                hasher.write(&blob);
+            }
+            count += 1;
+        }
+        Ok(format!("{}-{:016x}", count, hasher.finish()))
+    }
+
+    /// Similarity query over table alias `t` against the vector expression `source`.
+    ///
+    /// `extra_from` is appended to the FROM clause and `condition` to WHERE.
+    /// Parameters `?2` and `?3` are the page limit and offset, `?4` the cut-off
+    /// distance if the query has one (see `similar_params`).
+    fn similar_sql(&self, query: &SimilarityQuery, source: &str, extra_from: &str, condition: &str) -> String {
+        let t = |name| self.schema.col_of("t", name);
+        let embedding = t("embedding");
+        let (target, source, same_length) = match query.dims {
+            Some(dims) => (
+                format!("vector_slice({}, 0, {})", embedding, dims),
+                format!("vector_slice({}, 0, {})", source, dims),
+                String::new(),
+            ),
+            None => (
+                embedding.clone(),
+                source.to_string(),
+                format!(" AND length({}) = length({})", embedding, source),
+            ),
+        };
+        let candidates = match query.candidates {
+            Some(ref ids) if ids.is_empty() => " AND 0".to_string(),
+            Some(ref ids) => format!(" AND {} IN ({})", t("identifier"), id_list(ids)),
+            None => String::new(),
+        };
+        let threshold = match query.max_distance {
+            Some(_) => "WHERE dist <= ?4 ".to_string(),
+            None => String::new(),
+        };
+        format!(
+            "SELECT * FROM (SELECT {identifier}, {host}, COALESCE({summary}, ''), \
+             {distance}({target}, {source}) AS dist, \
+             COALESCE({link}, ''), {model}, {cost}, \
+             COALESCE({input_tokens}, 0), COALESCE({output_tokens}, 0), \
+             COALESCE({ts_start}, ''), COALESCE({ts_end}, '') \
+             FROM {table} t{extra_from} \
+             WHERE {embedding} IS NOT NULL AND {condition}{same_length}{candidates}) \
+             {threshold}ORDER BY dist LIMIT ?2 OFFSET ?3",
+            identifier = t("identifier"),
+            host = t("host"),
+            summary = t("summary"),
+            distance = query.metric.sql_function(),
+            link = t("original_source_link"),
+            model = t("model"),
+            cost = t("cost"),
+            input_tokens = t("summary_input_tokens"),
+            output_tokens = t("summary_output_tokens"),
+            ts_start = t("summary_timestamp_start"),
+            ts_end = t("summary_timestamp_end"),
+            table = self.schema.table_sql(),
+        )
+    }
 
+    /// Positional parameters for `similar_sql`, with `source` bound to `?1`.
+    fn similar_params(source: Value, query: &SimilarityQuery) -> turso::params::Params {
+        let mut params = vec![source, Value::Integer(query.limit), Value::Integer(query.offset)];
+        if let Some(max) = query.max_distance {
+            params.push(Value::Real(max));
+        }
+        turso::params::Params::Positional(params)
+    }
+
+    async fn collect_similar(mut rows: turso::Rows) -> turso::Result<Vec<SimilarResult>> {
+        let mut results = Vec::new();
         while let Some(row) = rows.next().await? {
             results.push(SimilarResult {
                 identifier: val_i64(&row.get_value(0)?),
@@ -222,7 +688,359 @@ impl Database {
                 summary_timestamp_end: val_string(&row.get_value(10)?),
             });
         }
-
         Ok(results)
     }
+
+    /// Ranked multi-term search across summary, transcript and timestamps.
+    /// The inverted index is built from the full text columns on the first call.
+    pub async fn search_fulltext(&self, query: &str, limit: usize) -> turso::Result<Vec<FulltextHit>> {
+        let index = self
+            .fulltext
+            .get_or_try_init(|| async { self.build_fulltext_index(&mut |_| {}).await })
+            .await?;
+        Ok(index.search(query, limit))
+    }
+
+    /// Build the full-text index from the text columns, calling `on_progress`
+    /// with the number of rows indexed so far every `FULLTEXT_PROGRESS_STEP` rows.
+    pub async fn build_fulltext_index(
+        &self,
+        on_progress: &mut (dyn FnMut(usize) + Send),
+    ) -> turso::Result<FulltextIndex> {
+        let mut index = FulltextIndex::new();
+        let c = |name| self.schema.col(name);
+        let sql = format!(
+            "SELECT {identifier}, COALESCE({summary}, ''), COALESCE({transcript}, ''), \
+             COALESCE({timestamps}, ''), COALESCE({youtube}, '') \
+             FROM {table} ORDER BY {identifier}",
+            identifier = c("identifier"),
+            summary = c("summary"),
+            transcript = c("transcript"),
+            timestamps = c("timestamps"),
+            youtube = c("timestamped_summary_in_youtube_format"),
+            table = self.schema.table_sql(),
+        );
+        let mut rows = self.conn.query(sql, ()).await?;
+
+        while let Some(row) = rows.next().await? {
+            let timestamps = format!(
+                "{}\n{}",
+                val_string(&row.get_value(3)?),
+                val_string(&row.get_value(4)?)
+            );
+            index.add_document(
+                val_i64(&row.get_value(0)?),
+                &val_string(&row.get_value(1)?),
+                &val_string(&row.get_value(2)?),
+                &timestamps,
+            );
+            if index.len().is_multiple_of(FULLTEXT_PROGRESS_STEP) {
+                on_progress(index.len());
+            }
+        }
+        Ok(index)
+    }
+
+    /// Every row as raw values of the logical columns (`schema::column_names()` order),
+    /// in identifier order; absent columns read as their defaults.
+    pub async fn dump_rows(&self) -> turso::Result<Vec<Vec<Value>>> {
+        let columns: Vec<String> = schema::column_names().map(|name| self.schema.col(name)).collect();
+        let mut rows = self
+            .conn
+            .query(
+                format!(
+                    "SELECT {columns} FROM {table} ORDER BY {identifier}",
+                    columns = columns.join(", "),
+                    table = self.schema.table_sql(),
+                    identifier = self.schema.col("identifier"),
+                ),
+                (),
+            )
+            .await?;
+
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().await? {
+            out.push((0..columns.len()).map(|i| row.get_value(i)).collect::<turso::Result<_>>()?);
+        }
+        Ok(out)
+    }
+
+    /// Fetch the next batch of transcript bodies after `after_id`, in identifier order.
+    pub async fn scan_transcripts(&self, after_id: i64, batch: i64) -> turso::Result<Vec<(i64, String)>> {
+        let mut out = Vec::new();
+        let mut rows = self
+            .conn
+            .query(
+                format!(
+                    "SELECT {identifier}, COALESCE({transcript}, '') FROM {table} \
+                     WHERE {identifier} > ?1 ORDER BY {identifier} LIMIT ?2",
+                    identifier = self.schema.col("identifier"),
+                    transcript = self.schema.col("transcript"),
+                    table = self.schema.table_sql(),
+                ),
+                turso::params::Params::Positional(vec![
+                    Value::Integer(after_id),
+                    Value::Integer(batch),
+                ]),
+            )
+            .await?;
+
+        while let Some(row) = rows.next().await? {
+            out.push((val_i64(&row.get_value(0)?), val_string(&row.get_value(1)?)));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_db(path: &Path, ddl: &str, inserts: &[&str]) {
+        let db = turso::Builder::new_local(&path.to_string_lossy()).build().await.unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute(ddl, ()).await.unwrap();
+        for sql in inserts {
+            conn.execute(*sql, ()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_current_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("current.db");
+        create_db(
+            &path,
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, model TEXT, host TEXT, \
+             original_source_link TEXT, output_language TEXT, summary TEXT, summary_done INTEGER, \
+             summary_input_tokens INTEGER, summary_output_tokens INTEGER, transcript TEXT, \
+             timestamps TEXT, timestamped_summary_in_youtube_format TEXT, cost REAL, embedding BLOB, \
+             embedding_model TEXT, summary_timestamp_start TEXT, summary_timestamp_end TEXT)",
+            &["INSERT INTO items (identifier, model, summary, transcript, cost) \
+               VALUES (1, 'm', 'About rust', 'we discuss tokio', 0.5)"],
+        )
+        .await;
+
+        let db = Database::open(&path).await.unwrap();
+        assert!(db.schema().warnings().is_empty());
+        let items = db.list_all_transcripts().await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].cost, 0.5);
+        let hits = db.search_fulltext("tokio", 10).await.unwrap();
+        assert_eq!(hits[0].identifier, 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_legacy_schema_degrades() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        create_db(
+            &path,
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, summary TEXT, link TEXT)",
+            &["INSERT INTO items (id, summary, link) VALUES (7, 'Old export', 'https://example.com')"],
+        )
+        .await;
+
+        let db = Database::open(&path).await.unwrap();
+        assert!(!db.schema().has_embeddings());
+        let items = db.list_all_transcripts().await.unwrap();
+        assert_eq!(items[0].identifier, 7);
+        assert_eq!(items[0].original_source_link, "https://example.com");
+        assert!(!items[0].has_embedding);
+        let row = db.get_transcript(7).await.unwrap().unwrap();
+        assert_eq!(row.summary, "Old export");
+        assert!(db.find_similar(7, &SimilarityQuery::default()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_open_image_copies_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy())
+            .build()
+            .await
+            .unwrap()
+            .connect()
+            .unwrap();
+        for sql in [
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, cost REAL, embedding BLOB)",
+            "CREATE INDEX items_cost ON items (cost)",
+            "INSERT INTO items VALUES (3, 'About rust', 0.25, x'0000803f')",
+            // Rows written before a column was added have shorter records
+            "ALTER TABLE items ADD COLUMN model TEXT DEFAULT 'unknown'",
+            "INSERT INTO items VALUES (-5, NULL, 2, NULL, 'flash')",
+        ] {
+            conn.execute(sql, ()).await.unwrap();
+        }
+        crate::import::checkpoint(&conn).await.unwrap();
+        drop(conn);
+
+        let image = std::fs::read(&path).unwrap();
+        let db = Database::open_image(&image).await.unwrap();
+        assert_eq!(
+            db.dump_rows().await.unwrap().len(),
+            Database::open(&path).await.unwrap().dump_rows().await.unwrap().len()
+        );
+        // A second connection sees the same in-memory database
+        let reader = db.reader().unwrap();
+        let mut rows = reader
+            .connection()
+            .query("SELECT identifier, summary, cost, embedding, model FROM items ORDER BY identifier", ())
+            .await
+            .unwrap();
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().await.unwrap() {
+            out.push((0..5).map(|i| row.get_value(i).unwrap()).collect::<Vec<_>>());
+        }
+        assert_eq!(
+            out,
+            vec![
+                vec![Value::Integer(-5), Value::Null, Value::Real(2.0), Value::Null, Value::Text("flash".into())],
+                vec![
+                    Value::Integer(3),
+                    Value::Text("About rust".into()),
+                    Value::Real(0.25),
+                    Value::Blob(vec![0, 0, 0x80, 0x3f]),
+                    Value::Text("unknown".into()),
+                ],
+            ]
+        );
+
+        assert!(matches!(Database::open_image(b"not a database").await, Err(OpenError::Image(_))));
+    }
+
+    #[tokio::test]
+    async fn test_open_image_empty_records_and_without_rowid_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.db");
+        create_db(
+            &path,
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT)",
+            &[
+                "CREATE TABLE e (a)",
+                "INSERT INTO e VALUES (NULL)",
+                // Turso cannot create WITHOUT ROWID tables; the last column is patched into the clause below
+                "CREATE TABLE w (k, v, pad_column_x)",
+                "CREATE INDEX w_k ON w (k)",
+                "INSERT INTO w (k, v) VALUES ('key', 'value')",
+            ],
+        )
+        .await;
+        let conn = turso::Builder::new_local(&path.to_string_lossy()).build().await.unwrap().connect().unwrap();
+        crate::import::checkpoint(&conn).await.unwrap();
+        drop(conn);
+
+        let mut image = std::fs::read(&path).unwrap();
+        let patch = |image: &mut [u8], from: &[u8], to: &[u8]| {
+            let at = image.windows(from.len()).position(|w| w == from).unwrap();
+            image[at..at + to.len()].copy_from_slice(to);
+        };
+        patch(&mut image, b"v, pad_column_x)", b"v) WITHOUT ROWID");
+        // Shrink the record header of the row in `e` (payload 2, rowid 1, header [2, NULL]) to no values
+        let page_size = sqlite_file::parse_header(&image).unwrap().page_size;
+        let root = sqlite_file::tables(&image).unwrap().iter().find(|t| t.name == "e").unwrap().root_page as usize;
+        patch(&mut image[(root - 1) * page_size..root * page_size], &[2, 1, 2, 0], &[2, 1, 1, 0]);
+
+        let db = Database::open_image(&image).await.unwrap();
+        assert_eq!(db.skipped_tables(), ["w"]);
+        assert!(db.warnings().iter().any(|w| w.contains("not loaded: w")));
+        let mut rows = db.conn.query("SELECT rowid, a FROM e", ()).await.unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!((row.get_value(0).unwrap(), row.get_value(1).unwrap()), (Value::Integer(1), Value::Null));
+        drop(rows);
+        let mut rows = db.conn.query("SELECT name FROM sqlite_master WHERE tbl_name = 'w'", ()).await.unwrap();
+        assert!(rows.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_open_reports_missing_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.db");
+        create_db(&path, "CREATE TABLE items (identifier INTEGER PRIMARY KEY, host TEXT)", &[]).await;
+
+        let err = Database::open(&path).await.err().unwrap();
+        assert!(matches!(err, OpenError::Schema(_)));
+        assert!(err.to_string().contains("summary"));
+    }
+
+    #[tokio::test]
+    async fn test_embedding_fingerprint_tracks_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fingerprint.db");
+        create_db(
+            &path,
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, embedding BLOB)",
+            &[
+                "INSERT INTO items VALUES (1, 'a', vector32('[1, 0]'))",
+                "INSERT INTO items VALUES (2, 'b', vector32('[0, 1]'))",
+            ],
+        )
+        .await;
+
+        let db = Database::open(&path).await.unwrap();
+        let before = db.embedding_fingerprint().await.unwrap();
+        assert_eq!(before, db.embedding_fingerprint().await.unwrap());
+        db.conn
+            .execute("UPDATE items SET embedding = vector32('[0, 2]') WHERE identifier = 2", ())
+            .await
+            .unwrap();
+        assert_ne!(before, db.embedding_fingerprint().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_to_centroid_excludes_seeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.db");
+        // 768-dim embeddings with weight on the first three axes.
+        let embedding = |x: f32, y: f32, z: f32| {
+            let mut v = vec![0.0f32; SIMILARITY_DIMS];
+            v[..3].copy_from_slice(&[x, y, z]);
+            let v: Vec<String> = v.iter().map(|f| f.to_string()).collect();
+            format!("vector32('[{}]')", v.join(","))
+        };
+        let rows = [
+            (1, (1.0, 0.0, 0.0)),
+            (2, (0.0, 1.0, 0.0)),
+            (3, (1.0, 1.0, 0.0)),
+            (4, (0.0, 0.0, 1.0)),
+        ];
+        let inserts: Vec<String> = rows
+            .iter()
+            .map(|(id, (x, y, z))| {
+                format!(
+                    "INSERT INTO items (identifier, summary, embedding) VALUES ({}, 's{}', {})",
+                    id, id, embedding(*x, *y, *z)
+                )
+            })
+            .collect();
+        let inserts: Vec<&str> = inserts.iter().map(String::as_str).collect();
+        create_db(
+            &path,
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, embedding BLOB)",
+            &inserts,
+        )
+        .await;
+
+        let db = Database::open(&path).await.unwrap();
+        let query = SimilarityQuery::default();
+        let (results, used) = db.find_similar_to_centroid(&[1, 2, 99], &query).await.unwrap();
+        assert_eq!(used, 2);
+        let ids: Vec<i64> = results.iter().map(|r| r.identifier).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert!(results[0].distance < 1e-4);
+
+        // Threshold, paging and another metric go through the same query.
+        let tight = SimilarityQuery { max_distance: Some(0.5), ..query.clone() };
+        let (results, _) = db.find_similar_to_centroid(&[1, 2], &tight).await.unwrap();
+        assert_eq!(results.len(), 1);
+        let second_page = SimilarityQuery { limit: 1, offset: 1, ..query.clone() };
+        let results = db.find_similar(1, &second_page).await.unwrap();
+        assert_eq!(results.len(), 1);
+        let l2 = SimilarityQuery { metric: Metric::L2, dims: None, ..query.clone() };
+        let results = db.find_similar(1, &l2).await.unwrap();
+        assert_eq!(results[0].identifier, 3);
+        let among = SimilarityQuery { candidates: Some(vec![2, 4]), ..query };
+        let results = db.find_similar(1, &among).await.unwrap();
+        assert_eq!(results.len(), 2);
+    }
 }
@@ -0,0 +1,274 @@
+use crate::ann::HnswIndex;
+use crate::db::TranscriptListItem;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Default cosine distance below which two summaries count as duplicates.
+pub const DEFAULT_MAX_DISTANCE: f32 = 0.05;
+
+/// Index neighbours inspected per transcript when comparing embeddings.
+const NEIGHBOURS: usize = 10;
+
+/// Why transcripts were put into the same cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    SameLink,
+    SimilarEmbedding,
+}
+
+impl DuplicateReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            DuplicateReason::SameLink => "same link",
+            DuplicateReason::SimilarEmbedding => "similar embedding",
+        }
+    }
+}
+
+/// A set of transcripts that are probably the same content.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateCluster {
+    /// Member identifiers, ascending.
+    pub members: Vec<i64>,
+    pub reasons: BTreeSet<DuplicateReason>,
+    /// Smallest embedding distance seen inside the cluster, if any.
+    pub min_distance: Option<f32>,
+}
+
+/// Normalize a source link so different spellings of the same video compare equal.
+///
+/// YouTube links (`youtu.be/ID`, `watch?v=ID`, `/shorts/ID`, `/embed/ID`,
+/// `m.` and `www.` hosts) become `youtube:ID`; other links lose their scheme,
+/// `www.`, fragment, tracking parameters and trailing slash.
+/// Returns `None` for empty links.
+pub fn canonical_link(link: &str) -> Option<String> {
+    let link = link.trim();
+    if link.is_empty() {
+        return None;
+    }
+    let without_scheme = link
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(link);
+    let without_fragment = without_scheme.split('#').next().unwrap_or("");
+    let (host_path, query) = without_fragment
+        .split_once('?')
+        .unwrap_or((without_fragment, ""));
+    let (host, path) = host_path.split_once('/').unwrap_or((host_path, ""));
+    let host = host.to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+
+    let params: Vec<(&str, &str)> = query
+        .split('&')
+        .filter_map(|p| p.split_once('=').or(Some((p, ""))))
+        .filter(|(k, _)| !k.is_empty())
+        .collect();
+
+    let youtube_id = match host {
+        "youtu.be" => path.split('/').next().map(str::to_string),
+        "youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = path.split('/');
+            match segments.next() {
+                Some("watch") => params.iter().find(|(k, _)| *k == "v").map(|(_, v)| v.to_string()),
+                Some("shorts") | Some("embed") | Some("live") | Some("v") => segments.next().map(str::to_string),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if let Some(id) = youtube_id.filter(|id| !id.is_empty()) {
+        return Some(format!("youtube:{}", id));
+    }
+
+    let kept: Vec<String> = params
+        .iter()
+        .filter(|(k, _)| !k.starts_with("utm_") && !matches!(*k, "si" | "feature" | "fbclid" | "gclid"))
+        .map(|(k, v)| if v.is_empty() { k.to_string() } else { format!("{}={}", k, v) })
+        .collect();
+    let path = path.trim_end_matches('/');
+    let mut canonical = if path.is_empty() { host.to_string() } else { format!("{}/{}", host, path) };
+    if !kept.is_empty() {
+        canonical = format!("{}?{}", canonical, kept.join("&"));
+    }
+    Some(canonical)
+}
+
+/// Disjoint-set forest over item positions.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self { parent: (0..n).collect() }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+}
+
+/// Group transcripts sharing a canonical source link or, if `index` is
+/// given, with embeddings within `max_distance` (cosine) of each other.
+///
+/// Clusters are transitive and returned largest first.
+pub fn find_duplicates(
+    items: &[TranscriptListItem],
+    index: Option<&HnswIndex>,
+    max_distance: f32,
+) -> Vec<DuplicateCluster> {
+    let positions: HashMap<i64, usize> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (item.identifier, i))
+        .collect();
+    let mut sets = UnionFind::new(items.len());
+    // (item, reason, distance) for every link that was made
+    let mut edges: Vec<(usize, DuplicateReason, Option<f32>)> = Vec::new();
+
+    let mut by_link: HashMap<String, usize> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        let Some(link) = canonical_link(&item.original_source_link) else {
+            continue;
+        };
+        match by_link.get(&link) {
+            Some(&first) => {
+                sets.union(first, i);
+                edges.push((i, DuplicateReason::SameLink, None));
+            }
+            None => {
+                by_link.insert(link, i);
+            }
+        }
+    }
+
+    if let Some(index) = index {
+        for (i, item) in items.iter().enumerate() {
+            let Some(v) = index.vector_of(item.identifier) else {
+                continue;
+            };
+            for (other, dist) in index.search(v, NEIGHBOURS) {
+                let Some(&j) = positions.get(&other) else {
+                    continue;
+                };
+                if j != i && dist <= max_distance {
+                    sets.union(i, j);
+                    edges.push((i, DuplicateReason::SimilarEmbedding, Some(dist)));
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, DuplicateCluster> = HashMap::new();
+    for (i, reason, dist) in edges {
+        let root = sets.find(i);
+        let cluster = clusters.entry(root).or_insert_with(|| DuplicateCluster {
+            members: Vec::new(),
+            reasons: BTreeSet::new(),
+            min_distance: None,
+        });
+        cluster.reasons.insert(reason);
+        if let Some(d) = dist.map(|d| d.max(0.0)) {
+            cluster.min_distance = Some(cluster.min_distance.map_or(d, |m| m.min(d)));
+        }
+    }
+    for (i, item) in items.iter().enumerate() {
+        let root = sets.find(i);
+        if let Some(cluster) = clusters.get_mut(&root) {
+            cluster.members.push(item.identifier);
+        }
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = clusters
+        .into_values()
+        .filter(|c| c.members.len() > 1)
+        .map(|mut c| {
+            c.members.sort_unstable();
+            c
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then(a.members[0].cmp(&b.members[0])));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(identifier: i64, link: &str) -> TranscriptListItem {
+        TranscriptListItem {
+            summary: format!("summary {}", identifier),
+            original_source_link: link.to_string(),
+            ..TranscriptListItem::fixture(identifier)
+        }
+    }
+
+    #[test]
+    fn test_canonical_link_youtube_variants() {
+        let expected = Some("youtube:dQw4w9WgXcQ".to_string());
+        for link in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42",
+            "http://m.youtube.com/watch?v=dQw4w9WgXcQ#t=1",
+            "https://youtu.be/dQw4w9WgXcQ?si=abc",
+            "youtube.com/shorts/dQw4w9WgXcQ",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(canonical_link(link), expected, "{}", link);
+        }
+    }
+
+    #[test]
+    fn test_canonical_link_other_sites() {
+        assert_eq!(
+            canonical_link("https://www.Example.com/talk/?utm_source=x&id=3#top"),
+            Some("example.com/talk?id=3".to_string())
+        );
+        assert_eq!(canonical_link("  "), None);
+    }
+
+    #[test]
+    fn test_find_duplicates_by_link_is_transitive() {
+        let items = vec![
+            item(1, "https://youtu.be/abc"),
+            item(2, "https://www.youtube.com/watch?v=abc"),
+            item(3, "https://youtu.be/other"),
+            item(4, "youtube.com/shorts/abc"),
+            item(5, ""),
+            item(6, ""),
+        ];
+        let clusters = find_duplicates(&items, None, DEFAULT_MAX_DISTANCE);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].members, vec![1, 2, 4]);
+        assert_eq!(clusters[0].reasons, BTreeSet::from([DuplicateReason::SameLink]));
+    }
+
+    #[test]
+    fn test_find_duplicates_by_embedding() {
+        let items = vec![item(1, "a.com/1"), item(2, "a.com/2"), item(3, "a.com/3")];
+        let index = HnswIndex::build(
+            3,
+            vec![(1, vec![1.0, 0.0, 0.0]), (2, vec![0.99, 0.01, 0.0]), (3, vec![0.0, 1.0, 0.0])],
+        );
+        let clusters = find_duplicates(&items, Some(&index), DEFAULT_MAX_DISTANCE);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].members, vec![1, 2]);
+        assert!(clusters[0].reasons.contains(&DuplicateReason::SimilarEmbedding));
+        assert!(clusters[0].min_distance.unwrap() < 0.01);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::app::get_display_title;
+use crate::db::{Database, TranscriptListItem, TranscriptRow};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("Failed to write export: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to read transcript: {0}")]
+    Database(#[from] turso::Error),
+
+    #[error("Failed to encode value: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Output format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON array of objects
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Human-readable digest, one section per entry
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Format implied by a file extension (`.json`, `.jsonl`/`.ndjson`, `.csv`, `.md`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// A field that can be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Identifier,
+    /// Display title derived from the summary
+    Title,
+    Link,
+    Host,
+    Model,
+    Summary,
+    Cost,
+    #[value(name = "input_tokens")]
+    InputTokens,
+    #[value(name = "output_tokens")]
+    OutputTokens,
+    #[value(name = "timestamp_start")]
+    TimestampStart,
+    #[value(name = "timestamp_end")]
+    TimestampEnd,
+    Bookmarked,
+    Tags,
+    /// Output language of the summary
+    Language,
+    /// Transcript text (reads the full row)
+    Transcript,
+    /// Timestamped summary (reads the full row)
+    Timestamps,
+}
+
+/// Columns exported when none are given.
+pub const DEFAULT_COLUMNS: [Column; 6] = [
+    Column::Identifier,
+    Column::Title,
+    Column::Link,
+    Column::Model,
+    Column::Cost,
+    Column::Summary,
+];
+
+impl Column {
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Identifier => "identifier",
+            Column::Title => "title",
+            Column::Link => "link",
+            Column::Host => "host",
+            Column::Model => "model",
+            Column::Summary => "summary",
+            Column::Cost => "cost",
+            Column::InputTokens => "input_tokens",
+            Column::OutputTokens => "output_tokens",
+            Column::TimestampStart => "timestamp_start",
+            Column::TimestampEnd => "timestamp_end",
+            Column::Bookmarked => "bookmarked",
+            Column::Tags => "tags",
+            Column::Language => "language",
+            Column::Transcript => "transcript",
+            Column::Timestamps => "timestamps",
+        }
+    }
+
+    /// Whether the value is only in the full row, not in the list cache.
+    fn needs_full_row(self) -> bool {
+        matches!(self, Column::Transcript | Column::Timestamps)
+    }
+
+    /// Long text shown as a paragraph rather than a key/value pair in Markdown.
+    fn is_body(self) -> bool {
+        matches!(self, Column::Summary | Column::Transcript | Column::Timestamps)
+    }
+
+    fn value(self, item: &TranscriptListItem, row: Option<&TranscriptRow>) -> Value {
+        let text = |s: &str| Value::String(s.to_string());
+        match self {
+            Column::Identifier => item.identifier.into(),
+            Column::Title => get_display_title(&item.summary).into(),
+            Column::Link => text(&item.original_source_link),
+            Column::Host => text(&item.host),
+            Column::Model => text(&item.model),
+            Column::Summary => text(&item.summary),
+            Column::Cost => item.cost.into(),
+            Column::InputTokens => item.summary_input_tokens.into(),
+            Column::OutputTokens => item.summary_output_tokens.into(),
+            Column::TimestampStart => text(&item.summary_timestamp_start),
+            Column::TimestampEnd => text(&item.summary_timestamp_end),
+            Column::Bookmarked => item.bookmarked.into(),
+            Column::Tags => item.tags.clone().into(),
+            Column::Language => text(&item.output_language),
+            Column::Transcript => row.map_or(Value::Null, |r| text(&r.transcript)),
+            // As in the detail view, plain timestamps stand in for a missing YouTube-formatted summary
+            Column::Timestamps => row.map_or(Value::Null, |r| {
+                if r.timestamped_summary_in_youtube_format.is_empty() {
+                    text(&r.timestamps)
+                } else {
+                    text(&r.timestamped_summary_in_youtube_format)
+                }
+            }),
+        }
+    }
+}
+
+/// Plain-text rendering of a value for CSV and Markdown.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(plain).collect::<Vec<_>>().join("; "),
+        other => other.to_string(),
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// JSON object with keys in column order.
+fn json_object(columns: &[Column], values: &[Value]) -> Result<String, serde_json::Error> {
+    let fields = columns
+        .iter()
+        .zip(values)
+        .map(|(c, v)| Ok(format!("{}:{}", serde_json::to_string(c.name())?, serde_json::to_string(v)?)))
+        .collect::<Result<Vec<_>, serde_json::Error>>()?;
+    Ok(format!("{{{}}}", fields.join(",")))
+}
+
+fn write_markdown_entry(out: &mut impl Write, columns: &[Column], values: &[Value]) -> std::io::Result<()> {
+    let get = |col: Column| columns.iter().position(|c| *c == col).map(|i| plain(&values[i]));
+    let heading = get(Column::Title)
+        .or_else(|| get(Column::Identifier).map(|id| format!("#{}", id)))
+        .unwrap_or_else(|| "Entry".to_string());
+    match get(Column::Link).filter(|l| !l.is_empty()) {
+        Some(link) => writeln!(out, "## [{}]({})\n", heading, link)?,
+        None => writeln!(out, "## {}\n", heading)?,
+    }
+
+    let meta: Vec<String> = columns
+        .iter()
+        .zip(values)
+        .filter(|(c, _)| !c.is_body() && !matches!(c, Column::Title | Column::Link))
+        .map(|(c, v)| format!("**{}**: {}", c.name(), plain(v)))
+        .collect();
+    if !meta.is_empty() {
+        writeln!(out, "{}\n", meta.join(" · "))?;
+    }
+    for (c, v) in columns.iter().zip(values).filter(|(c, _)| c.is_body()) {
+        if *c != Column::Summary {
+            writeln!(out, "### {}\n", c.name())?;
+        }
+        writeln!(out, "{}\n", plain(v).trim())?;
+    }
+    writeln!(out, "---\n")
+}
+
+/// Write `items` as `format`, one record per item with `columns` in order.
+///
+/// Full rows are read from `db` only if a column needs them.
+pub async fn write_export(
+    db: &Database,
+    items: &[&TranscriptListItem],
+    columns: &[Column],
+    format: ExportFormat,
+    out: &mut impl Write,
+) -> Result<(), ExportError> {
+    let needs_row = columns.iter().any(|c| c.needs_full_row());
+    match format {
+        ExportFormat::Json => writeln!(out, "[")?,
+        ExportFormat::Csv => {
+            let header: Vec<&str> = columns.iter().map(|c| c.name()).collect();
+            writeln!(out, "{}", header.join(","))?;
+        }
+        ExportFormat::Markdown => writeln!(out, "# Transcript export\n\n{} entries\n", items.len())?,
+        ExportFormat::Jsonl => {}
+    }
+
+    for (n, item) in items.iter().enumerate() {
+        let row = if needs_row { db.get_transcript(item.identifier).await? } else { None };
+        let values: Vec<Value> = columns.iter().map(|c| c.value(item, row.as_ref())).collect();
+        match format {
+            ExportFormat::Json => {
+                let sep = if n + 1 < items.len() { "," } else { "" };
+                writeln!(out, "  {}{}", json_object(columns, &values)?, sep)?;
+            }
+            ExportFormat::Jsonl => writeln!(out, "{}", json_object(columns, &values)?)?,
+            ExportFormat::Csv => {
+                let fields: Vec<String> = values.iter().map(|v| csv_field(&plain(v))).collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+            ExportFormat::Markdown => write_markdown_entry(out, columns, &values)?,
+        }
+    }
+
+    if format == ExportFormat::Json {
+        writeln!(out, "]")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(identifier: i64, summary: &str) -> TranscriptListItem {
+        TranscriptListItem {
+            host: "h".to_string(),
+            summary: summary.to_string(),
+            cost: 0.5,
+            model: "m".to_string(),
+            original_source_link: format!("https://youtu.be/{}", identifier),
+            output_language: "en".to_string(),
+            summary_input_tokens: 10,
+            summary_output_tokens: 20,
+            bookmarked: true,
+            tags: vec!["a".to_string(), "b".to_string()],
+            ..TranscriptListItem::fixture(identifier)
+        }
+    }
+
+    async fn export(format: ExportFormat, columns: &[Column]) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy()).build().await.unwrap().connect().unwrap();
+        conn.execute(
+            "CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, transcript TEXT, \
+             timestamps TEXT, timestamped_summary_in_youtube_format TEXT)",
+            (),
+        )
+        .await
+        .unwrap();
+        conn.execute(
+            "INSERT INTO items VALUES (1, 'x', 'spoken words', '0:00 raw', '0:00 Intro'), (2, 'y', NULL, '0:00 raw', '')",
+            (),
+        )
+        .await
+        .unwrap();
+        let db = Database::open(&path).await.unwrap();
+        let (a, b) = (item(1, "First, \"quoted\"\nmore"), item(2, "Second"));
+        let mut out = Vec::new();
+        write_export(&db, &[&a, &b], columns, format, &mut out).await.unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_json_and_jsonl_keep_column_order() {
+        let columns = [Column::Identifier, Column::Tags, Column::Cost];
+        let json = export(ExportFormat::Json, &columns).await;
+        let parsed: Vec<Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["tags"], serde_json::json!(["a", "b"]));
+
+        let jsonl = export(ExportFormat::Jsonl, &columns).await;
+        let first = jsonl.lines().next().unwrap();
+        assert_eq!(first, r#"{"identifier":1,"tags":["a","b"],"cost":0.5}"#);
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_quotes_fields() {
+        let csv = export(ExportFormat::Csv, &[Column::Identifier, Column::Summary, Column::Tags]).await;
+        assert!(csv.starts_with("identifier,summary,tags\n1,\"First, \"\"quoted\"\"\nmore\",a; b\n"));
+        assert!(csv.ends_with("2,Second,a; b\n"));
+    }
+
+    #[tokio::test]
+    async fn test_export_markdown_digest() {
+        let md = export(ExportFormat::Markdown, &DEFAULT_COLUMNS).await;
+        assert!(md.starts_with("# Transcript export\n\n2 entries\n"));
+        assert!(md.contains("## [Second](https://youtu.be/2)\n\n**identifier**: 2 · **model**: m · **cost**: 0.5\n\nSecond\n"));
+    }
+
+    #[tokio::test]
+    async fn test_export_reads_full_rows_when_needed() {
+        let jsonl = export(ExportFormat::Jsonl, &[Column::Identifier, Column::Transcript]).await;
+        assert_eq!(jsonl, "{\"identifier\":1,\"transcript\":\"spoken words\"}\n{\"identifier\":2,\"transcript\":\"\"}\n");
+    }
+
+    #[tokio::test]
+    async fn test_export_timestamps_fall_back_to_plain() {
+        let jsonl = export(ExportFormat::Jsonl, &[Column::Identifier, Column::Timestamps]).await;
+        assert_eq!(jsonl, "{\"identifier\":1,\"timestamps\":\"0:00 Intro\"}\n{\"identifier\":2,\"timestamps\":\"0:00 raw\"}\n");
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ExportFormat::from_path(Path::new("out.NDJSON")), Some(ExportFormat::Jsonl));
+        assert_eq!(ExportFormat::from_path(Path::new("digest.md")), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_path(Path::new("out")), None);
+    }
+}
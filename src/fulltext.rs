@@ -0,0 +1,341 @@
+use crate::db::Database;
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+
+/// Which column of the `items` table a term occurred in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Summary,
+    Transcript,
+    Timestamps,
+}
+
+impl Field {
+    pub const ALL: [Field; 3] = [Self::Summary, Self::Transcript, Self::Timestamps];
+
+    /// Relative weight of a hit in this field. A term in the summary says more
+    /// about what a video is about than a passing mention in the transcript.
+    fn weight(self) -> f64 {
+        match self {
+            Self::Summary => 2.0,
+            Self::Transcript => 1.0,
+            Self::Timestamps => 1.5,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Summary => 0,
+            Self::Transcript => 1,
+            Self::Timestamps => 2,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Summary => "summary",
+            Self::Transcript => "transcript",
+            Self::Timestamps => "timestamps",
+        }
+    }
+}
+
+/// One entry of a posting list: how often a term occurs in one field of one document.
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    doc: u32,
+    field: Field,
+    tf: u32,
+}
+
+/// A ranked full-text search hit.
+#[derive(Debug, Clone)]
+pub struct FulltextHit {
+    pub identifier: i64,
+    pub score: f64,
+    /// Number of distinct query terms found in the document.
+    pub matched_terms: usize,
+    /// Field that contributed most to the score.
+    pub best_field: Field,
+}
+
+// BM25 tuning constants.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Inverted index over summary, transcript and timestamp text.
+#[derive(Debug, Default)]
+pub struct FulltextIndex {
+    identifiers: Vec<i64>,
+    /// Token count per document and field.
+    lengths: Vec<[u32; 3]>,
+    total_lengths: [u64; 3],
+    postings: HashMap<String, Vec<Posting>>,
+}
+
+/// Split text into lowercase alphanumeric terms, dropping single characters.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() > 1)
+        .map(|t| t.to_lowercase())
+}
+
+impl FulltextIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document. Each field is tokenized and counted separately.
+    pub fn add_document(&mut self, identifier: i64, summary: &str, transcript: &str, timestamps: &str) {
+        let doc = self.identifiers.len() as u32;
+        self.identifiers.push(identifier);
+
+        let mut lengths = [0u32; 3];
+        for (field, text) in [
+            (Field::Summary, summary),
+            (Field::Transcript, transcript),
+            (Field::Timestamps, timestamps),
+        ] {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for term in tokenize(text) {
+                *counts.entry(term).or_insert(0) += 1;
+                lengths[field.index()] += 1;
+            }
+            for (term, tf) in counts {
+                self.postings.entry(term).or_default().push(Posting { doc, field, tf });
+            }
+        }
+
+        for (total, len) in self.total_lengths.iter_mut().zip(lengths) {
+            *total += len as u64;
+        }
+        self.lengths.push(lengths);
+    }
+
+    pub fn len(&self) -> usize {
+        self.identifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.identifiers.is_empty()
+    }
+
+    /// Rank documents against a multi-term query.
+    ///
+    /// Documents matching more distinct terms always rank above documents
+    /// matching fewer; within the same count they are ordered by a
+    /// field-weighted BM25 score.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<FulltextHit> {
+        let mut terms: Vec<String> = tokenize(query).collect();
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() || self.is_empty() {
+            return Vec::new();
+        }
+
+        let n = self.identifiers.len() as f64;
+        let avg_len: Vec<f64> = self
+            .total_lengths
+            .iter()
+            .map(|&t| (t as f64 / n).max(1.0))
+            .collect();
+
+        // doc -> (score, matched terms, per-field score)
+        let mut acc: HashMap<u32, (f64, usize, [f64; 3])> = HashMap::new();
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+
+            let mut docs_with_term: Vec<u32> = postings.iter().map(|p| p.doc).collect();
+            docs_with_term.dedup();
+            let df = docs_with_term.len() as f64;
+            let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            for p in postings {
+                let len = self.lengths[p.doc as usize][p.field.index()] as f64;
+                let tf = p.tf as f64;
+                let norm = tf * (K1 + 1.0)
+                    / (tf + K1 * (1.0 - B + B * len / avg_len[p.field.index()]));
+                let s = idf * norm * p.field.weight();
+
+                let entry = acc.entry(p.doc).or_insert((0.0, 0, [0.0; 3]));
+                entry.0 += s;
+                entry.2[p.field.index()] += s;
+            }
+            for doc in docs_with_term {
+                if let Some(entry) = acc.get_mut(&doc) {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let mut hits: Vec<FulltextHit> = acc
+            .into_iter()
+            .map(|(doc, (score, matched_terms, per_field))| {
+                let best_field = Field::ALL
+                    .into_iter()
+                    .max_by(|a, b| per_field[a.index()].total_cmp(&per_field[b.index()]))
+                    .unwrap_or(Field::Summary);
+                FulltextHit {
+                    identifier: self.identifiers[doc as usize],
+                    score,
+                    matched_terms,
+                    best_field,
+                }
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.matched_terms
+                .cmp(&a.matched_terms)
+                .then(b.score.total_cmp(&a.score))
+                .then(a.identifier.cmp(&b.identifier))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Messages sent from the background index build to the TUI thread.
+enum BuildMessage {
+    /// Number of rows indexed so far
+    Progress(usize),
+    Done(Result<FulltextIndex, String>),
+}
+
+/// Handle to the full-text index being built in the background.
+///
+/// Dropping the handle aborts the task.
+pub struct FulltextBuild {
+    pub indexed: usize,
+    pub total: usize,
+    rx: Receiver<BuildMessage>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl FulltextBuild {
+    /// Start indexing the `total` rows of `db` on a separate connection.
+    pub fn spawn(db: &Database, total: usize) -> turso::Result<Self> {
+        let reader = db.reader()?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let task = tokio::spawn(async move {
+            let progress = tx.clone();
+            let result = reader
+                .build_fulltext_index(&mut |indexed| {
+                    let _ = progress.send(BuildMessage::Progress(indexed));
+                })
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(BuildMessage::Done(result));
+        });
+        Ok(Self { indexed: 0, total, rx, task })
+    }
+
+    /// Drain pending messages; returns the finished index (or error) once available.
+    pub fn poll(&mut self) -> Option<Result<FulltextIndex, String>> {
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                BuildMessage::Progress(indexed) => self.indexed = indexed,
+                BuildMessage::Done(result) => return Some(result),
+            }
+        }
+        None
+    }
+
+    /// Fraction of rows indexed, in `0.0..=1.0`.
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.indexed as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+impl Drop for FulltextBuild {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_index() -> FulltextIndex {
+        let mut index = FulltextIndex::new();
+        index.add_document(1, "Rust async runtimes explained", "today we talk about tokio", "");
+        index.add_document(2, "Cooking pasta", "boil water, add salt. rust never sleeps", "");
+        index.add_document(3, "Kernel news", "the linux kernel now accepts rust drivers", "00:01 rust in the kernel");
+        index
+    }
+
+    #[test]
+    fn test_tokenize_lowercases_and_splits() {
+        let terms: Vec<String> = tokenize("Hello, World! a x-ray").collect();
+        assert_eq!(terms, vec!["hello", "world", "ray"]);
+    }
+
+    #[test]
+    fn test_search_finds_transcript_only_terms() {
+        let index = sample_index();
+        let hits = index.search("tokio", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].identifier, 1);
+        assert_eq!(hits[0].best_field, Field::Transcript);
+    }
+
+    #[test]
+    fn test_search_ranks_more_matched_terms_first() {
+        let index = sample_index();
+        let hits = index.search("rust kernel", 10);
+        assert_eq!(hits[0].identifier, 3);
+        assert_eq!(hits[0].matched_terms, 2);
+        assert_eq!(hits.len(), 3);
+    }
+
+    #[test]
+    fn test_search_prefers_summary_hits() {
+        let index = sample_index();
+        let hits = index.search("rust", 10);
+        let pos = |id| hits.iter().position(|h| h.identifier == id).unwrap();
+        assert!(pos(1) < pos(2));
+        assert_eq!(hits[pos(1)].best_field, Field::Summary);
+    }
+
+    #[test]
+    fn test_search_empty_query_and_limit() {
+        let index = sample_index();
+        assert!(index.search("", 10).is_empty());
+        assert!(index.search("unknownterm", 10).is_empty());
+        assert_eq!(index.search("rust", 2).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fulltext_build_in_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("t.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy()).build().await.unwrap().connect().unwrap();
+        conn.execute("CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, transcript TEXT)", ())
+            .await
+            .unwrap();
+        for i in 0..450 {
+            conn.execute("INSERT INTO items VALUES (?1, 'talk', ?2)", (i, if i == 7 { "tokio" } else { "other" }))
+                .await
+                .unwrap();
+        }
+        let db = Database::open(&path).await.unwrap();
+
+        let mut build = FulltextBuild::spawn(&db, 450).unwrap();
+        let index = loop {
+            if let Some(result) = build.poll() {
+                break result.unwrap();
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        };
+        assert_eq!(index.len(), 450);
+        assert_eq!(build.indexed, 400);
+        assert_eq!(index.search("tokio", 5)[0].identifier, 7);
+    }
+}
@@ -0,0 +1,100 @@
+//! `--health-check`: a quick self-test the updater runs on a freshly
+//! installed binary before it commits to it.
+
+use crate::codec;
+use crate::db::Database;
+use crate::import;
+use age::secrecy::Secret;
+use std::path::Path;
+
+/// Bytes pushed through the codec; repetitive so Brotli has something to do.
+const CODEC_SAMPLE: &[u8] = b"SQLite format 3\0health check payload, health check payload, health check payload";
+
+/// Create a one-row database in the current schema and read it back through `Database`.
+async fn check_database(dir: &Path) -> Result<(), String> {
+    let path = dir.join("health.db");
+    let conn = turso::Builder::new_local(&path.to_string_lossy())
+        .build()
+        .await
+        .and_then(|db| db.connect())
+        .map_err(|e| format!("cannot create SQLite database: {}", e))?;
+    conn.execute(import::CREATE_ITEMS_TABLE, ())
+        .await
+        .map_err(|e| format!("cannot create table: {}", e))?;
+    conn.execute(
+        "INSERT INTO items (identifier, summary, transcript, cost) VALUES (1, 'health', 'check', 0.5)",
+        (),
+    )
+    .await
+    .map_err(|e| format!("cannot insert row: {}", e))?;
+    drop(conn);
+
+    let db = Database::open(&path).await.map_err(|e| e.to_string())?;
+    let items = db.list_all_transcripts().await.map_err(|e| e.to_string())?;
+    let row = db.get_transcript(1).await.map_err(|e| e.to_string())?;
+    match (items.len(), row) {
+        (1, Some(row)) if row.transcript == "check" => Ok(()),
+        (n, _) => Err(format!("read back {} rows instead of the one written", n)),
+    }
+}
+
+/// Encrypt and compress a sample, then decrypt it and compare.
+fn check_codec(dir: &Path) -> Result<(), String> {
+    let plain = dir.join("sample.bin");
+    let encrypted = dir.join("sample.age");
+    let decrypted = dir.join("sample.out");
+    std::fs::write(&plain, CODEC_SAMPLE).map_err(|e| e.to_string())?;
+    let password = "health-check";
+    codec::encrypt_stream(&plain, &encrypted, &codec::Encryption::Passphrase(Secret::new(password.to_string())), &codec::Compression::new(1))
+        .map_err(|e| format!("encryption failed: {}", e))?;
+    if !codec::is_age_file(&encrypted) {
+        return Err("encrypted file has no age header".to_string());
+    }
+    codec::decode_stream(&encrypted, &decrypted, Some(&codec::Credentials::Passphrase(Secret::new(password.to_string()))), &mut |_| {})
+        .map_err(|e| format!("decryption failed: {}", e))?;
+    match std::fs::read(&decrypted) {
+        Ok(bytes) if bytes == CODEC_SAMPLE => Ok(()),
+        Ok(_) => Err("round trip changed the data".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Run every check, printing one line each to stdout. Returns whether all passed.
+pub async fn run() -> bool {
+    println!("transcript-explorer {}", env!("CARGO_PKG_VERSION"));
+    let dir = match tempfile::tempdir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("temporary directory: FAILED: {}", e);
+            return false;
+        }
+    };
+
+    let results = [
+        ("database", check_database(dir.path()).await),
+        ("codec", check_codec(dir.path())),
+    ];
+    let mut ok = true;
+    for (name, result) in results {
+        match result {
+            Ok(()) => println!("{}: ok", name),
+            Err(e) => {
+                println!("{}: FAILED: {}", name, e);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health_checks_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(check_database(dir.path()).await, Ok(()));
+        assert_eq!(check_codec(dir.path()), Ok(()));
+    }
+}
@@ -0,0 +1,440 @@
+//! Building an `items` database from subtitle and transcript files.
+//!
+//! Each file becomes one row with `transcript` and `timestamps` filled in;
+//! link, language and host come from a sidecar JSON file when there is one,
+//! and the summary fields are left empty for a later pipeline run.
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The `items` table in the layout `Schema` expects without aliases.
+pub const CREATE_ITEMS_TABLE: &str = "CREATE TABLE IF NOT EXISTS items (identifier INTEGER PRIMARY KEY, \
+    model TEXT, host TEXT, original_source_link TEXT, output_language TEXT, summary TEXT, \
+    summary_done INTEGER, summary_input_tokens INTEGER, summary_output_tokens INTEGER, transcript TEXT, \
+    timestamps TEXT, timestamped_summary_in_youtube_format TEXT, cost REAL, embedding BLOB, \
+    embedding_model TEXT, summary_timestamp_start TEXT, summary_timestamp_end TEXT)";
+
+/// Sidecar keys holding the source link, in order of preference
+/// (`webpage_url` is what yt-dlp writes to `.info.json`).
+const LINK_KEYS: [&str; 5] = ["original_source_link", "webpage_url", "original_url", "link", "url"];
+const LANGUAGE_KEYS: [&str; 3] = ["output_language", "language", "lang"];
+const HOST_KEYS: [&str; 1] = ["host"];
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Failed to read {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error("Failed to parse {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error("Database error: {0}")]
+    Database(#[from] turso::Error),
+}
+
+/// File formats `import` reads, by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    /// SubRip (`.srt`)
+    Srt,
+    /// WebVTT (`.vtt`)
+    Vtt,
+    /// YouTube timed text (`.json3`)
+    Json3,
+    /// Plain text without timing (`.txt`)
+    Text,
+}
+
+impl SourceFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::Vtt),
+            "json3" => Some(Self::Json3),
+            "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// One timed caption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start_ms: u64,
+    pub text: String,
+}
+
+/// A transcript file read into the columns it fills.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedTranscript {
+    pub transcript: String,
+    pub timestamps: String,
+    pub link: String,
+    pub language: String,
+    pub host: String,
+}
+
+/// Parse `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm` into milliseconds.
+fn parse_time(s: &str) -> Option<u64> {
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    let (h, m, sec) = match parts[..] {
+        [h, m, s] => (h.parse::<u64>().ok()?, m, s),
+        [m, s] => (0, m, s),
+        _ => return None,
+    };
+    let m: u64 = m.parse().ok()?;
+    let (s, ms) = sec.split_once([',', '.']).unwrap_or((sec, "0"));
+    let ms: u64 = format!("{:0<3}", ms).get(..3)?.parse().ok()?;
+    Some(((h * 60 + m) * 60 + s.parse::<u64>().ok()?) * 1000 + ms)
+}
+
+/// `MM:SS`, or `H:MM:SS` from the first hour on, as in the pipeline's `timestamps` column.
+pub fn format_time(ms: u64) -> String {
+    let s = ms / 1000;
+    if s >= 3600 {
+        format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+    } else {
+        format!("{:02}:{:02}", s / 60, s % 60)
+    }
+}
+
+/// Remove markup such as `<i>`, `<c.colorE5E5E5>` or inline `<00:00:01.200>` times
+/// and decode the entities subtitle files use.
+fn strip_markup(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for ch in line.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(ch),
+            _ => {}
+        }
+    }
+    out.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Cues of an SRT or WebVTT file: blocks separated by blank lines, each with a
+/// `start --> end` line followed by the text. Blocks without one (indices,
+/// `WEBVTT`, `NOTE`, `STYLE`) are skipped.
+fn parse_cue_blocks(content: &str) -> Result<Vec<Cue>, String> {
+    let content = content.replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines();
+        let Some(timing) = lines.by_ref().find(|l| l.contains("-->")) else {
+            continue;
+        };
+        let start = timing.split("-->").next().unwrap_or_default();
+        let start_ms = parse_time(start).ok_or_else(|| format!("invalid cue timing \"{}\"", timing.trim()))?;
+        let text: Vec<String> = lines.map(strip_markup).filter(|l| !l.is_empty()).collect();
+        if !text.is_empty() {
+            cues.push(Cue { start_ms, text: text.join("\n") });
+        }
+    }
+    Ok(cues)
+}
+
+/// Cues of a YouTube `json3` file (`events[].tStartMs` with `segs[].utf8`).
+fn parse_json3(content: &str) -> Result<Vec<Cue>, String> {
+    let json: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let events = json["events"].as_array().ok_or("no \"events\" array")?;
+    Ok(events
+        .iter()
+        .filter_map(|event| {
+            let start_ms = event["tStartMs"].as_u64()?;
+            let text: String = event["segs"].as_array()?.iter().filter_map(|s| s["utf8"].as_str()).collect();
+            let text = text.trim();
+            (!text.is_empty()).then(|| Cue { start_ms, text: text.to_string() })
+        })
+        .collect())
+}
+
+/// Transcript text and `timestamps` lines from cues.
+///
+/// Auto-generated captions repeat the previous line at the top of each cue;
+/// a line equal to the one before it is dropped.
+fn join_cues(cues: &[Cue]) -> (String, String) {
+    let mut words: Vec<&str> = Vec::new();
+    let mut stamps: Vec<String> = Vec::new();
+    let mut last = "";
+    for cue in cues {
+        let fresh: Vec<&str> = cue.text.lines().filter(|&l| l != last).collect();
+        if let Some(l) = cue.text.lines().last() {
+            last = l;
+        }
+        if fresh.is_empty() {
+            continue;
+        }
+        stamps.push(format!("{} {}", format_time(cue.start_ms), fresh.join(" ")));
+        words.extend(fresh);
+    }
+    (words.join(" "), stamps.join("\n"))
+}
+
+/// Language code in a subtitle file name such as `talk.en.vtt` or `talk.pt-BR.srt`.
+fn language_from_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let (_, code) = stem.rsplit_once('.')?;
+    let (lang, region) = code.split_once('-').unwrap_or((code, ""));
+    let valid = (2..=3).contains(&lang.len())
+        && lang.bytes().all(|b| b.is_ascii_lowercase())
+        && region.bytes().all(|b| b.is_ascii_alphanumeric());
+    valid.then(|| code.to_string())
+}
+
+/// Sidecar JSON for `path`: `<prefix>.info.json` or `<prefix>.json` for the file
+/// name with one or more extensions removed, longest prefix first.
+fn find_sidecar(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let mut prefix = name;
+    while let Some((shorter, _)) = prefix.rsplit_once('.') {
+        prefix = shorter;
+        for suffix in [".info.json", ".json"] {
+            let candidate = path.with_file_name(format!("{}{}", prefix, suffix));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+/// First non-empty string among `keys` in a sidecar object.
+fn sidecar_field(sidecar: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|k| sidecar[*k].as_str().map(str::trim).filter(|s| !s.is_empty()))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> ImportError {
+    ImportError::Io { path: path.to_path_buf(), source }
+}
+
+fn parse_error(path: &Path, message: impl Into<String>) -> ImportError {
+    ImportError::Parse { path: path.to_path_buf(), message: message.into() }
+}
+
+/// Read one transcript file and its sidecar.
+pub fn read_transcript(path: &Path) -> Result<ImportedTranscript, ImportError> {
+    let format = SourceFormat::from_path(path)
+        .ok_or_else(|| parse_error(path, "unsupported file type (expected .srt, .vtt, .json3 or .txt)"))?;
+    let content = std::fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+    let content = content.trim_start_matches('\u{feff}');
+
+    let mut imported = match format {
+        SourceFormat::Text => ImportedTranscript { transcript: content.trim().to_string(), ..Default::default() },
+        SourceFormat::Srt | SourceFormat::Vtt | SourceFormat::Json3 => {
+            let cues = if format == SourceFormat::Json3 {
+                parse_json3(content)
+            } else {
+                parse_cue_blocks(content)
+            }
+            .map_err(|e| parse_error(path, e))?;
+            let (transcript, timestamps) = join_cues(&cues);
+            ImportedTranscript { transcript, timestamps, ..Default::default() }
+        }
+    };
+    if imported.transcript.is_empty() {
+        return Err(parse_error(path, "no transcript text"));
+    }
+
+    if let Some(sidecar_path) = find_sidecar(path) {
+        let text = std::fs::read_to_string(&sidecar_path).map_err(|e| io_error(&sidecar_path, e))?;
+        let sidecar: Value = serde_json::from_str(&text).map_err(|e| parse_error(&sidecar_path, e.to_string()))?;
+        imported.link = sidecar_field(&sidecar, &LINK_KEYS);
+        imported.language = sidecar_field(&sidecar, &LANGUAGE_KEYS);
+        imported.host = sidecar_field(&sidecar, &HOST_KEYS);
+    }
+    if imported.language.is_empty() {
+        imported.language = language_from_name(path).unwrap_or_default();
+    }
+    Ok(imported)
+}
+
+/// Transcript files among `paths`, descending into directories, in path order.
+///
+/// Files given explicitly are kept whatever their extension so that `read_transcript`
+/// can report them; inside directories only supported files are picked up.
+pub fn collect_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, ImportError> {
+    fn walk(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), ImportError> {
+        for entry in std::fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+            let path = entry.map_err(|e| io_error(dir, e))?.path();
+            if path.is_dir() {
+                walk(&path, out)?;
+            } else if SourceFormat::from_path(&path).is_some() {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            walk(path, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Move the contents of the write-ahead log into the main database file.
+pub async fn checkpoint(conn: &turso::Connection) -> turso::Result<()> {
+    let mut rows = conn.query("PRAGMA wal_checkpoint(TRUNCATE)", ()).await?;
+    while rows.next().await?.is_some() {}
+    Ok(())
+}
+
+/// Writes imported transcripts into a plain database, creating the `items`
+/// table if needed. Rows are added in one transaction, committed by `finish`.
+pub struct Importer {
+    conn: turso::Connection,
+    next_id: i64,
+    links: HashSet<String>,
+}
+
+impl Importer {
+    pub async fn open(path: &Path) -> Result<Self, ImportError> {
+        let db = turso::Builder::new_local(&path.to_string_lossy()).build().await?;
+        let conn = db.connect()?;
+        conn.execute(CREATE_ITEMS_TABLE, ()).await?;
+
+        let mut next_id = 1;
+        let mut rows = conn.query("SELECT COALESCE(MAX(identifier), 0) FROM items", ()).await?;
+        if let Some(row) = rows.next().await? {
+            next_id = row.get::<i64>(0)? + 1;
+        }
+        drop(rows);
+
+        let mut links = HashSet::new();
+        let mut rows = conn
+            .query("SELECT original_source_link FROM items WHERE COALESCE(original_source_link, '') != ''", ())
+            .await?;
+        while let Some(row) = rows.next().await? {
+            links.insert(row.get::<String>(0)?);
+        }
+        drop(rows);
+
+        conn.execute("BEGIN", ()).await?;
+        Ok(Self { conn, next_id, links })
+    }
+
+    /// Add one row; returns its identifier, or `None` if a row with the same
+    /// source link already exists.
+    pub async fn insert(&mut self, t: &ImportedTranscript) -> Result<Option<i64>, ImportError> {
+        if !t.link.is_empty() && !self.links.insert(t.link.clone()) {
+            return Ok(None);
+        }
+        let id = self.next_id;
+        self.conn
+            .execute(
+                "INSERT INTO items (identifier, model, host, original_source_link, output_language, summary, \
+                 summary_done, summary_input_tokens, summary_output_tokens, transcript, timestamps, \
+                 timestamped_summary_in_youtube_format, cost, embedding, embedding_model, \
+                 summary_timestamp_start, summary_timestamp_end) \
+                 VALUES (?1, '', ?2, ?3, ?4, '', 0, 0, 0, ?5, ?6, '', 0, NULL, '', '', '')",
+                (
+                    id,
+                    t.host.as_str(),
+                    t.link.as_str(),
+                    t.language.as_str(),
+                    t.transcript.as_str(),
+                    t.timestamps.as_str(),
+                ),
+            )
+            .await?;
+        self.next_id += 1;
+        Ok(Some(id))
+    }
+
+    /// Commit the rows and fold the write-ahead log into the database file,
+    /// so the file can be copied or encrypted on its own.
+    pub async fn finish(self) -> Result<(), ImportError> {
+        self.conn.execute("COMMIT", ()).await?;
+        checkpoint(&self.conn).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_parse_srt_and_vtt() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:03,000\r\n<i>Hello</i> there\r\n\r\n2\r\n01:02:03,000 --> 01:02:04,000\r\nGeneral Kenobi\r\n";
+        let cues = parse_cue_blocks(srt).unwrap();
+        assert_eq!(cues, vec![
+            Cue { start_ms: 1500, text: "Hello there".into() },
+            Cue { start_ms: 3_723_000, text: "General Kenobi".into() },
+        ]);
+        assert_eq!(join_cues(&cues).1, "00:01 Hello there\n1:02:03 General Kenobi");
+
+        // Rolling auto-captions repeat the previous line
+        let vtt = "WEBVTT\nKind: captions\n\nNOTE a comment\n\n00:05.000 --> 00:07.000 align:start\nfirst <00:00:05.500><c>line</c>\n\n\
+                   00:07.000 --> 00:09.000\nfirst line\nsecond &amp; last\n";
+        let (transcript, timestamps) = join_cues(&parse_cue_blocks(vtt).unwrap());
+        assert_eq!(transcript, "first line second & last");
+        assert_eq!(timestamps, "00:05 first line\n00:07 second & last");
+        assert!(parse_cue_blocks("1\nxx --> yy\ntext").is_err());
+    }
+
+    #[test]
+    fn test_parse_json3() {
+        let json = r#"{"events":[{"tStartMs":0,"segs":[{"utf8":"hi"},{"utf8":" all"}]},
+            {"tStartMs":900,"segs":[{"utf8":"\n"}]},{"tStartMs":61000,"segs":[{"utf8":"bye"}]}]}"#;
+        let (transcript, timestamps) = join_cues(&parse_json3(json).unwrap());
+        assert_eq!(transcript, "hi all bye");
+        assert_eq!(timestamps, "00:00 hi all\n01:01 bye");
+    }
+
+    #[tokio::test]
+    async fn test_import_with_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("talk.en.srt"), "1\n00:00:02,000 --> 00:00:03,000\nhello\n").unwrap();
+        std::fs::write(
+            dir.path().join("talk.info.json"),
+            r#"{"webpage_url": "https://youtu.be/x", "url": "https://cdn/x.mp4", "language": null}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "plain words\n").unwrap();
+        std::fs::write(dir.path().join("cover.jpg"), "").unwrap();
+
+        let files = collect_files(&[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(files.len(), 2);
+        let talk = read_transcript(&dir.path().join("talk.en.srt")).unwrap();
+        assert_eq!((talk.link.as_str(), talk.language.as_str()), ("https://youtu.be/x", "en"));
+
+        let path = dir.path().join("out.db");
+        for _ in 0..2 {
+            let mut importer = Importer::open(&path).await.unwrap();
+            for file in &files {
+                importer.insert(&read_transcript(file).unwrap()).await.unwrap();
+            }
+            importer.finish().await.unwrap();
+        }
+
+        // The second run only adds the file without a link again
+        let db = Database::open(&path).await.unwrap();
+        assert!(db.schema().warnings().is_empty());
+        let items = db.list_all_transcripts().await.unwrap();
+        assert_eq!(items.len(), 3);
+        let row = db.get_transcript(2).await.unwrap().unwrap();
+        assert_eq!((row.transcript.as_str(), row.timestamps.as_str()), ("hello", "00:02 hello"));
+        assert_eq!(row.summary, "");
+    }
+}
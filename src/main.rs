@@ -1,8 +1,26 @@
+mod ann;
+mod annotations;
+mod cleanup;
 mod app;
+mod cli;
 mod codec;
 mod db;
+mod duplicates;
+mod export;
+mod fulltext;
+mod health;
+mod import;
+mod merge;
+mod query;
+mod schema;
+mod settings;
+mod sqlite_file;
+mod stats;
+mod topics;
+mod transcript_search;
 mod ui;
 mod update;
+mod vector;
 
 use app::{App, DetailTab, InputMode, View};
 use clap::{Parser, Subcommand};
@@ -28,6 +46,22 @@ struct Cli {
     /// Password for encrypted database files
     #[arg(short, long)]
     password: Option<String>,
+
+    /// Decrypt encrypted databases into memory instead of a temporary file,
+    /// so no plaintext copy is written to disk (also `database.decrypt_in_memory`
+    /// in settings.json); `merge` and `cleanup` then refuse `--encrypt`
+    #[arg(long, global = true)]
+    in_memory: bool,
+
+    /// age identity file (from age-keygen) for databases encrypted to recipients;
+    /// repeatable [default: identity.txt in the config directory]
+    #[arg(long, global = true, value_name = "FILE")]
+    identity: Vec<PathBuf>,
+
+    /// Check that this binary can open databases and decrypt exports, print
+    /// the version and exit (non-zero on failure); used by the self-updater
+    #[arg(long)]
+    health_check: bool,
 }
 
 const DEFAULT_DB_URL: &str = "https://rocketrecap.com/exports/summaries20260123.age";
@@ -36,7 +70,7 @@ const DEFAULT_DB_URL: &str = "https://rocketrecap.com/exports/summaries20260123.
 enum Commands {
     /// Run the TUI explorer (default)
     Run {
-        /// Path to the SQLite database file
+        /// Path to the database file: plain SQLite, compressed or age-encrypted
         #[arg(short, long)]
         db: PathBuf,
     },
@@ -48,16 +82,29 @@ enum Commands {
         /// Output encrypted file
         #[arg(short, long)]
         output: PathBuf,
-        /// Fast compression (quality 1) works best for speed
+        /// Compression inside the encrypted file; Run and Decrypt detect it
+        #[arg(long, value_enum, default_value_t = codec::Codec::Brotli)]
+        codec: codec::Codec,
+        /// Fast compression (the codec's lowest level) works best for speed
         #[arg(long, conflicts_with = "best")]
         fast: bool,
-        /// Best compression (quality 11) works best for size but is slow
+        /// Best compression (the codec's highest level) works best for size but is slow
         #[arg(long, conflicts_with = "fast")]
         best: bool,
+        /// Brotli input compressed per thread at a time, in MiB (1-1024)
+        #[arg(long, default_value_t = (codec::DEFAULT_CHUNK_SIZE / (1024 * 1024)) as u64, value_parser = clap::value_parser!(u64).range(1..=1024))]
+        chunk_size: u64,
+        /// Compression threads [default: one per CPU]
+        #[arg(long)]
+        threads: Option<usize>,
+        #[command(flatten)]
+        recipients: cli::RecipientArgs,
     },
     /// Decrypt and decompress a database file
+    ///
+    /// The input may be age-encrypted (with any codec inside) or only compressed with Brotli, zstd or gzip.
     Decrypt {
-        /// Input encrypted file
+        /// Input encrypted or compressed file
         #[arg(short, long)]
         input: PathBuf,
         /// Output database file
@@ -67,11 +114,203 @@ enum Commands {
         #[arg(short, long)]
         password: Option<String>,
     },
+    /// Create or extend a database from SRT, WebVTT, YouTube JSON3 or plain-text transcripts
+    ///
+    /// Link, language and host are read from a sidecar `<name>.info.json` or `<name>.json`
+    /// next to each file; summary fields are left empty.
+    Import {
+        /// Database file to create or append to (plain SQLite)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Transcript files or directories to search for them
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Combine several databases (plain or .age) into a new one
+    ///
+    /// Entries with the same canonical source link and model are kept once (the most
+    /// recently summarised wins); colliding identifiers of later inputs are reassigned.
+    Merge {
+        /// Databases to combine; on identifier collisions earlier ones keep theirs
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+        /// New database file to write
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Compress and encrypt the result with the password (or to --recipient keys)
+        #[arg(long)]
+        encrypt: bool,
+        #[command(flatten)]
+        recipients: cli::RecipientArgs,
+    },
+    /// Write a smaller copy of a database for sharing
+    ///
+    /// Removes rows whose summary is a processing error ("Error: ..."), empties the large
+    /// text columns and truncates embeddings, then vacuums the result.
+    Cleanup {
+        /// Database to clean (plain or .age); it is not modified
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Cleaned database to write
+        #[arg(short, long, required_unless_present = "dry_run")]
+        output: Option<PathBuf>,
+        /// Text columns to empty, comma separated
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "transcript,timestamps,youtube-timestamps", conflicts_with = "keep_text")]
+        strip: Vec<cleanup::StripColumn>,
+        /// Keep all text columns
+        #[arg(long)]
+        keep_text: bool,
+        /// Truncate embeddings to this many float32 values
+        #[arg(long, default_value_t = db::SIMILARITY_DIMS, conflicts_with = "keep_embeddings")]
+        embedding_dims: usize,
+        /// Keep embeddings at full length
+        #[arg(long)]
+        keep_embeddings: bool,
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+        /// Compress and encrypt the result with the password (or to --recipient keys)
+        #[arg(long)]
+        encrypt: bool,
+        #[command(flatten)]
+        recipients: cli::RecipientArgs,
+    },
+    /// Report the format of a database file and check that it opens
+    ///
+    /// Shows whether the file is plain SQLite, age (passphrase or recipients) or Brotli, and
+    /// the scrypt work factor. With a password the whole stream is decrypted in memory and
+    /// checked for a valid SQLite database; no plaintext is written to disk.
+    Inspect {
+        /// File to inspect
+        file: PathBuf,
+        /// Decrypt and check an encrypted file, prompting for the password if not given
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Report near-duplicate transcripts (same source link or very similar embedding)
+    Duplicates {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Maximum cosine distance between embeddings of duplicates
+        #[arg(long, default_value_t = duplicates::DEFAULT_MAX_DISTANCE)]
+        max_distance: f32,
+        /// Only compare canonicalised source links, not embeddings
+        #[arg(long)]
+        links_only: bool,
+        /// Print the clusters as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print matching rows as a table or JSON lines; exits with 1 if nothing matched, 2 on errors
+    Search {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Free-text filter over summary, host and link (as with `/` in the TUI)
+        query: Option<String>,
+        /// Filter expression, e.g. `cost=0.01..0.5`, `model=gemini*`, `tag=ai|bookmarked=yes`,
+        /// `!host=foo`; repeat to combine with AND
+        #[arg(short, long = "filter")]
+        filters: Vec<String>,
+        /// Rank by full-text relevance over summaries, transcripts and timestamps (as `F` in the TUI)
+        #[arg(long)]
+        fulltext: bool,
+        /// Collapse consecutive entries with identical summaries
+        #[arg(long)]
+        group: bool,
+        /// Print at most this many rows (or groups)
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        /// Print JSON lines instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the nearest neighbours of a transcript by embedding
+    Similar {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Identifier of the transcript to find neighbours for
+        #[arg(long)]
+        id: i64,
+        /// Number of neighbours
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+        /// Distance metric
+        #[arg(long, value_enum, default_value_t = vector::Metric::Cosine)]
+        metric: vector::Metric,
+        /// Leave out neighbours farther away than this distance
+        #[arg(long)]
+        max_distance: Option<f64>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = cli::similar::SimilarFormat::Tsv)]
+        format: cli::similar::SimilarFormat,
+    },
+    /// Print one transcript with its metadata
+    Show {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Identifier of the transcript
+        #[arg(long)]
+        id: i64,
+        /// Sections to print, comma separated
+        #[arg(short, long, value_enum, value_delimiter = ',', default_value = "summary")]
+        section: Vec<app::DetailTab>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = cli::show::ShowFormat::Text)]
+        format: cli::show::ShowFormat,
+    },
+    /// Report cost and token statistics per model, host, language or month
+    Stats {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Free-text filter over summary, host and link (as with `/` in the TUI)
+        #[arg(short = 'q', long)]
+        query: Option<String>,
+        /// Filter expression, e.g. `cost=0.01..0.5`, `model=gemini*`; repeat to combine with AND
+        #[arg(short, long = "filter")]
+        filters: Vec<String>,
+        /// Breakdowns to report, comma separated
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "model,host,language,month")]
+        by: Vec<stats::GroupBy>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export matching rows as JSON, JSONL, CSV or a Markdown digest
+    Export {
+        /// Path to the database file (plain or .age)
+        #[arg(short, long)]
+        db: PathBuf,
+        /// Free-text filter over summary, host and link (as with `/` in the TUI)
+        #[arg(short = 'q', long)]
+        query: Option<String>,
+        /// Filter expression, e.g. `cost=0.01..0.5`, `model=gemini*`, `tag=ai|bookmarked=yes`,
+        /// `!host=foo`; repeat to combine with AND
+        #[arg(short, long = "filter")]
+        filters: Vec<String>,
+        /// Columns to export, comma separated [default: identifier,title,link,model,cost,summary]
+        #[arg(short, long, value_enum, value_delimiter = ',')]
+        columns: Vec<export::Column>,
+        /// Output format [default: from the output extension, else json]
+        #[arg(long, value_enum)]
+        format: Option<export::ExportFormat>,
+        /// Output file (standard output if omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+
+    if cli.health_check {
+        std::process::exit(if health::run().await { 0 } else { 1 });
+    }
     
     // Normalize command
     let command = match cli.command {
@@ -89,29 +328,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    let settings_path = settings::Settings::default_path();
+    let settings = match settings_path.as_deref().map(settings::Settings::load) {
+        Some(Ok(settings)) => settings,
+        Some(Err(e)) => {
+            eprintln!("Warning: {}; using defaults", e);
+            settings::Settings::default()
+        }
+        None => settings::Settings::default(),
+    };
+    let in_memory = cli.in_memory || settings.database.decrypt_in_memory;
+    let keys = cli::Keys { password: cli.password.as_deref(), identities: &cli.identity };
+
     match command {
-        Commands::Encrypt { input, output, fast, best } => {
+        Commands::Encrypt { input, output, codec, fast, best, chunk_size, threads, recipients } => {
             if !input.exists() {
                 eprintln!("Error: input file not found: {}", input.display());
                 std::process::exit(1);
             }
             
+            let [fastest, default, smallest] = codec.qualities();
             let quality = if fast {
-                1
+                fastest
             } else if best {
-                11
+                smallest
             } else {
-                6 // Default
+                default
             };
 
-            let password = if let Some(p) = cli.password.as_ref() {
-                p.clone()
-            } else {
-                eprint!("Enter password: ");
-                read_password_with_stars()?
+            let encryption = recipients.encryption(cli.password.as_deref(), "Enter password: ")?;
+            let compression = codec::Compression {
+                codec,
+                quality,
+                chunk_size: chunk_size as usize * 1024 * 1024,
+                threads: threads.unwrap_or(0),
             };
-            eprintln!("Encrypting {} -> {} (quality: {})...", input.display(), output.display(), quality);
-            codec::encrypt_stream(&input, &output, Secret::new(password), quality)?;
+            eprintln!("Encrypting {} -> {} ({} quality: {})...", input.display(), output.display(), codec.label(), quality);
+            codec::encrypt_stream(&input, &output, &encryption, &compression)?;
             eprintln!("Done.");
         }
         Commands::Decrypt { input, output, password } => {
@@ -119,18 +372,87 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 eprintln!("Error: input file not found: {}", input.display());
                 std::process::exit(1);
             }
-            let password = if let Some(p) = password {
-                p
-            } else if let Some(p) = cli.password.as_ref() {
-                p.clone()
-            } else {
-                eprint!("Enter password: ");
-                read_password_with_stars()?
+            let keys = cli::Keys {
+                password: password.as_deref().or(cli.password.as_deref()),
+                identities: &cli.identity,
             };
+            let kind = codec::detect(&input)?;
+            if !kind.is_encoded() {
+                eprintln!("Error: {} is neither encrypted nor compressed", input.display());
+                std::process::exit(1);
+            }
+            let credentials = keys.credentials(&kind)?;
             eprintln!("Decrypting {} -> {} ...", input.display(), output.display());
-            codec::decrypt_stream(&input, &output, Secret::new(password))?;
+            let start = std::time::Instant::now();
+            codec::decode_stream(&input, &output, credentials.as_ref(), &mut cli::phase_timer())?;
+            eprintln!("Total Decrypt Time: {:?}", start.elapsed());
             eprintln!("Done.");
         }
+        Commands::Duplicates { db, max_distance, links_only, json } => {
+            let opened = cli::open_database(&db, &keys, in_memory).await?;
+            let args = cli::duplicates::DuplicatesArgs { max_distance, links_only, json };
+            cli::duplicates::run(&opened.db, &args).await?;
+        }
+        Commands::Search { db, query, filters, fulltext, group, limit, json } => {
+            let args = cli::search::SearchArgs {
+                text: query.unwrap_or_default(),
+                filters,
+                fulltext,
+                group,
+                limit,
+                json,
+            };
+            let result = match cli::open_database(&db, &keys, in_memory).await {
+                Ok(opened) => cli::search::run(&opened.db, &args, &mut std::io::stdout().lock()).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result {
+                eprintln!("Error: {}", e);
+            }
+            match cli::search::exit_code(&result) {
+                0 => {}
+                code => std::process::exit(code),
+            }
+        }
+        Commands::Similar { db, id, limit, metric, max_distance, format } => {
+            let opened = cli::open_database(&db, &keys, in_memory).await?;
+            let args = cli::similar::SimilarArgs { id, limit, metric, max_distance, format };
+            cli::similar::run(&opened.db, &args, &mut std::io::stdout().lock()).await?;
+        }
+        Commands::Show { db, id, section, format } => {
+            let opened = cli::open_database(&db, &keys, in_memory).await?;
+            let args = cli::show::ShowArgs { id, sections: section, format };
+            cli::show::run(&opened.db, &args, &mut std::io::stdout().lock()).await?;
+        }
+        Commands::Import { db, paths } => {
+            cli::import::run(&db, &paths).await?;
+        }
+        Commands::Merge { inputs, output, encrypt, recipients } => {
+            let encrypt = (encrypt || !recipients.is_empty()).then_some(&recipients);
+            cli::merge::run(&inputs, &output, encrypt, &keys, in_memory).await?;
+        }
+        Commands::Cleanup { input, output, strip, keep_text, embedding_dims, keep_embeddings, dry_run, encrypt, recipients } => {
+            let options = cleanup::CleanupOptions {
+                strip: if keep_text { Vec::new() } else { strip },
+                embedding_dims: (!keep_embeddings).then_some(embedding_dims),
+            };
+            let encrypt = (encrypt || !recipients.is_empty()).then_some(recipients);
+            let args = cli::cleanup::CleanupArgs { input, output, options, dry_run, encrypt, in_memory };
+            cli::cleanup::run(&args, &keys).await?;
+        }
+        Commands::Inspect { file, verify } => {
+            cli::inspect::run(&file, verify, &keys)?;
+        }
+        Commands::Stats { db, query, filters, by, json } => {
+            let opened = cli::open_database(&db, &keys, in_memory).await?;
+            let args = cli::stats::StatsArgs { text: query.unwrap_or_default(), filters, by, json };
+            cli::stats::run(&opened.db, &args, &mut std::io::stdout().lock()).await?;
+        }
+        Commands::Export { db, query, filters, columns, format, output } => {
+            let opened = cli::open_database(&db, &keys, in_memory).await?;
+            let args = cli::export::ExportArgs { text: query.unwrap_or_default(), filters, columns, format, output };
+            cli::export::run(&opened.db, &args).await?;
+        }
         Commands::Run { db } => {
             let mut db_path = db;
             
@@ -163,106 +485,146 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Actually, we can just check the header bytes. SQLite header is "SQLite format 3\0".
             // Age header is "age-encryption.org".
             
-            let mut is_encrypted = false;
-            if let Ok(mut file) = std::fs::File::open(&db_path) {
-                use std::io::Read;
-                let mut buffer = [0u8; 18]; // "age-encryption.org" length
-                if file.read_exact(&mut buffer).is_ok() {
-                     if &buffer == b"age-encryption.org" {
-                         is_encrypted = true;
-                     }
-                }
-            }
+            let kind = codec::detect(&db_path).unwrap_or(codec::FileKind::Unknown);
             
-            let _temp_file; // Keep alive until function end
+            // Decoded database file; kept alive until the function ends and
+            // dropped (deleting the plaintext) before any early exit
+            let mut temp_file = None;
+            // Decoded database when decoding into memory
+            let mut image = None;
             
-            let target_db_path = if is_encrypted {
-                eprintln!("Detected encrypted database: {}", db_path.display());
+            let target_db_path = if kind.is_encoded() {
+                if kind.is_encrypted() {
+                    eprintln!("Detected encrypted database: {}", db_path.display());
+                } else {
+                    eprintln!("Detected compressed database: {}", db_path.display());
+                }
                 
                 // Initialize terminal early for password input
                 let mut terminal = ratatui::init();
                 
-                let password = if let Some(p) = cli.password {
-                    p
+                // Files encrypted to recipients open with identity files, without a prompt
+                let mut credentials = if let codec::FileKind::AgeRecipients { .. } = kind {
+                    match keys.identities() {
+                        Ok(identities) => Some(codec::Credentials::Identities(identities)),
+                        Err(e) => {
+                            ratatui::restore();
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
                 } else {
-                    // Use TUI-based password input
-                    let mut password_overlay = ui::password_overlay::PasswordInputOverlay::new(
-                        "Enter database password:".to_string()
-                    );
+                    None
+                };
+                
+                // A wrong passphrase is asked for again, up to the configured number of attempts;
+                // a password given with --password counts as the first one
+                let mut password_overlay = ui::password_overlay::PasswordInputOverlay::new(
+                    "Enter database password:".to_string()
+                );
+                let mut given_password = keys.password.map(str::to_string);
+                let max_attempts = settings.database.password_attempts.max(1);
+                let mut attempts = 0;
+                
+                let title = if kind.is_encrypted() { "Decrypting Database" } else { "Decompressing Database" };
+                let destination = if in_memory { "into memory" } else { "to temporary file" };
+                let temp = if in_memory { None } else { Some(tempfile::NamedTempFile::new()?) };
+                
+                let decoded = loop {
+                    if let codec::FileKind::AgePassphrase { .. } = kind {
+                        let password = match given_password.take() {
+                            Some(p) => p,
+                            None => match prompt_password(&mut terminal, &mut password_overlay)? {
+                                Some(p) => p,
+                                None => {
+                                    ratatui::restore();
+                                    drop(temp);
+                                    eprintln!("Password entry cancelled");
+                                    std::process::exit(1);
+                                }
+                            },
+                        };
+                        credentials = Some(codec::Credentials::Passphrase(Secret::new(password)));
+                        attempts += 1;
+                    }
                     
-                    let mut password_result = None;
+                    // Show decoding progress; decoding runs on this thread, so the
+                    // screen is redrawn from the callback, at most once per half percent
+                    terminal.draw(|frame| ui::decode_overlay::render(frame, title, destination, None))?;
+                    let mut drawn = None;
+                    let mut on_progress = |progress: codec::Progress| {
+                        let step = (progress.phase, (progress.ratio() * 200.0) as u32);
+                        if drawn != Some(step) {
+                            drawn = Some(step);
+                            let _ = terminal.draw(|frame| ui::decode_overlay::render(frame, title, destination, Some(progress)));
+                        }
+                    };
                     
-                    loop {
-                        terminal.draw(|frame| {
-                            password_overlay.render(frame);
-                        })?;
-                        
-                        if crossterm::event::poll(std::time::Duration::from_millis(100))? {
-                            match event::read()? {
-                                Event::Key(key) => {
-                                    if key.kind != KeyEventKind::Press {
-                                        continue;
-                                    }
-                                    
-                                    match password_overlay.handle_key(key) {
-                                        Some(ui::password_overlay::PasswordInputResult::Submit(pwd)) => {
-                                            password_result = Some(pwd);
-                                            break;
-                                        }
-                                        Some(ui::password_overlay::PasswordInputResult::Cancel) => {
-                                            ratatui::restore();
-                                            eprintln!("Password entry cancelled");
-                                            std::process::exit(1);
-                                        }
-                                        None => {}
-                                    }
-                                }
-                                _ => {}
+                    let result = match &temp {
+                        None => codec::decode_to_memory(&db_path, credentials.as_ref(), &mut on_progress).map(Some),
+                        Some(temp) => codec::decode_stream(&db_path, temp.path(), credentials.as_ref(), &mut on_progress).map(|_| None),
+                    };
+                    match result {
+                        Ok(bytes) => break bytes,
+                        Err(codec::DecodeError::WrongPassphrase) if attempts < max_attempts => {
+                            let left = max_attempts - attempts;
+                            password_overlay.set_error(format!(
+                                "Wrong password, {} attempt{} left",
+                                left,
+                                if left == 1 { "" } else { "s" }
+                            ));
+                        }
+                        Err(e) => {
+                            ratatui::restore();
+                            // `process::exit` skips destructors; remove the partial plaintext first
+                            drop(temp);
+                            match e.hint() {
+                                Some(hint) => eprintln!("Error: Failed to decode database: {}\n{}", e, hint),
+                                None => eprintln!("Error: Failed to decode database: {}", e),
                             }
+                            std::process::exit(1);
                         }
                     }
-                    
-                    password_result.unwrap_or_default()
                 };
                 
-                // Show decryption status in the overlay
-                terminal.draw(|frame| {
-                    let area = frame.size();
-                    let block = ratatui::widgets::Block::default()
-                        .title("Decrypting Database")
-                        .borders(ratatui::widgets::Borders::ALL)
-                        .border_style(ratatui::style::Style::default().fg(ratatui::style::Color::Cyan));
-                    
-                    let text = ratatui::widgets::Paragraph::new("Decrypting to temporary file...")
-                        .block(block)
-                        .alignment(ratatui::layout::Alignment::Center);
-                    
-                    frame.render_widget(ratatui::widgets::Clear, area);
-                    frame.render_widget(text, area);
-                })?;
-                
-                let temp = tempfile::NamedTempFile::new()?;
-                
-                match codec::decrypt_stream(&db_path, temp.path(), Secret::new(password.clone())) {
-                    Ok(_) => {
-                        _temp_file = temp; // extend lifetime
-                        _temp_file.path().to_path_buf()
+                match (decoded, temp) {
+                    (Some(bytes), _) => {
+                        image = Some(bytes);
+                        db_path
                     }
-                    Err(e) => {
-                        ratatui::restore();
-                        eprintln!("Error: Failed to decrypt database: {}", e);
-                        std::process::exit(1);
+                    (None, temp) => {
+                        let temp = temp_file.insert(temp.expect("decoded to the temporary file"));
+                        temp.path().to_path_buf()
                     }
                 }
             } else {
                 db_path
             };
 
-            // Open database
-            let database = db::Database::open(&target_db_path).await?;
+            // Open database; the decoded image is released once it is loaded
+            let opened = match image.take() {
+                Some(image) => db::Database::open_image(&image).await,
+                None => db::Database::open(&target_db_path).await,
+            };
+            let database = match opened {
+                Ok(database) => database,
+                Err(e) => {
+                    ratatui::restore();
+                    drop(temp_file);
+                    eprintln!("Error: Failed to open database: {}", e);
+                    std::process::exit(1);
+                }
+            };
 
             // Create app
             let mut app = App::new(database);
+            if let Some(path) = annotations::AnnotationStore::default_path() {
+                match annotations::AnnotationStore::open(&path).await {
+                    Ok(store) => app.set_annotation_store(store),
+                    Err(e) => eprintln!("Warning: Failed to open annotation store {}: {}", path.display(), e),
+                }
+            }
+            app.set_settings(settings, settings_path);
             app.init().await?;
 
             // Create update channels
@@ -311,6 +673,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             ratatui::restore();
 
             if let Err(e) = result {
+                drop(temp_file);
                 eprintln!("Error: {e}");
                 std::process::exit(1);
             }
@@ -327,6 +690,10 @@ async fn run_app(
     loop {
         // Poll for update messages at the start of each loop iteration
         app.poll_update_messages();
+        app.poll_transcript_search();
+        app.poll_ann_index();
+        app.poll_topics();
+        app.poll_fulltext_index()?;
 
         terminal.draw(|frame| ui::render(app, frame))?;
 
@@ -384,11 +751,18 @@ async fn handle_key(app: &mut App, key: KeyEvent) -> Result<(), Box<dyn std::err
         handle_filter_input(app, key).await?;
         return Ok(());
     }
+    if app.input_mode.is_prompt() {
+        handle_prompt_input(app, key).await?;
+        return Ok(());
+    }
     match app.view {
         View::List => handle_list_key(app, key).await?,
         View::Detail => handle_detail_key(app, key).await?,
         View::Similar => handle_similar_key(app, key).await?,
         View::Filters => handle_filters_key(app, key).await?,
+        View::Duplicates => handle_duplicates_key(app, key).await?,
+        View::Topics => handle_topics_key(app, key).await?,
+        View::Stats => handle_stats_key(app, key),
     }
 
     Ok(())
@@ -423,6 +797,33 @@ async fn handle_filter_input(
     Ok(())
 }
 
+async fn handle_prompt_input(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match key.code {
+        KeyCode::Enter if app.input_mode == InputMode::EditingExportPath => {
+            if let Err(e) = app.commit_export().await {
+                app.status_msg = e.to_string();
+            }
+        }
+        KeyCode::Enter => {
+            app.commit_annotation().await?;
+        }
+        KeyCode::Esc => {
+            app.cancel_annotation();
+        }
+        KeyCode::Backspace => {
+            app.prompt_buffer.pop();
+        }
+        KeyCode::Char(c) => {
+            app.prompt_buffer.push(c);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 async fn handle_list_key(app: &mut App, key: KeyEvent) -> Result<(), Box<dyn std::error::Error>> {
     match key.code {
         KeyCode::Char('q') => {
@@ -452,6 +853,39 @@ async fn handle_list_key(app: &mut App, key: KeyEvent) -> Result<(), Box<dyn std
         KeyCode::Char('s') => {
             app.open_similar().await?;
         }
+        KeyCode::Char('m') => {
+            app.toggle_mark();
+        }
+        KeyCode::Char('S') => {
+            app.open_centroid_similar().await?;
+        }
+        KeyCode::Char('F') => {
+            app.apply_fulltext_search()?;
+        }
+        KeyCode::Char('D') => {
+            app.open_duplicates()?;
+        }
+        KeyCode::Char('T') => {
+            app.open_topics()?;
+        }
+        KeyCode::Char('C') => {
+            app.open_stats();
+        }
+        KeyCode::Char('E') => {
+            app.begin_export();
+        }
+        KeyCode::Char('t') => {
+            app.toggle_transcript_search();
+        }
+        KeyCode::Char('b') => {
+            app.toggle_bookmark().await?;
+        }
+        KeyCode::Char('#') => {
+            app.begin_annotation(InputMode::EditingTags);
+        }
+        KeyCode::Char('n') => {
+            app.begin_annotation(InputMode::EditingNote);
+        }
         KeyCode::Char('f') => {
             app.view = View::Filters;
             app.status_msg.clear();
@@ -489,8 +923,7 @@ async fn handle_detail_key(
 ) -> Result<(), Box<dyn std::error::Error>> {
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') => {
-            app.view = View::List;
-            app.detail = None;
+            app.close_detail();
         }
         KeyCode::Down | KeyCode::Char('j') => {
             app.scroll_down();
@@ -527,6 +960,15 @@ async fn handle_detail_key(
         KeyCode::Char('s') => {
             app.open_similar().await?;
         }
+        KeyCode::Char('b') => {
+            app.toggle_bookmark().await?;
+        }
+        KeyCode::Char('#') => {
+            app.begin_annotation(InputMode::EditingTags);
+        }
+        KeyCode::Char('n') => {
+            app.begin_annotation(InputMode::EditingNote);
+        }
         KeyCode::Char('y') => {
             if let Some(ref detail) = app.detail {
                 // Try to copy link to clipboard using xclip/xsel/wl-copy
@@ -590,6 +1032,24 @@ async fn handle_similar_key(
         KeyCode::Enter => {
             app.open_detail().await?;
         }
+        KeyCode::Char('l') => {
+            app.load_more_similar().await?;
+        }
+        KeyCode::Char('M') => {
+            app.cycle_similarity_metric().await?;
+        }
+        KeyCode::Char('D') => {
+            app.cycle_similarity_dims().await?;
+        }
+        KeyCode::Char('T') => {
+            app.toggle_similarity_cutoff().await?;
+        }
+        KeyCode::Char('I') => {
+            app.toggle_similarity_index().await?;
+        }
+        KeyCode::Char('R') => {
+            app.toggle_similarity_rerank().await?;
+        }
         KeyCode::Char('y') => {
             if let Some(group) = app.grouped_similar_results.get(app.similar_selected) {
                 if let Some(res) = group.items.first() {
@@ -639,6 +1099,100 @@ async fn handle_similar_key(
     Ok(())
 }
 
+async fn handle_duplicates_key(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.view = View::List;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.duplicates_next(1);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.duplicates_prev(1);
+        }
+        KeyCode::PageDown => {
+            app.duplicates_next(app.page_size);
+        }
+        KeyCode::PageUp => {
+            app.duplicates_prev(app.page_size);
+        }
+        KeyCode::Char('b') => {
+            app.toggle_bookmark().await?;
+        }
+        KeyCode::Enter => {
+            app.open_detail().await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn handle_topics_key(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            if app.topic_open.is_some() {
+                app.topic_open = None;
+            } else {
+                app.view = View::List;
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.topics_next(1);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.topics_prev(1);
+        }
+        KeyCode::PageDown => {
+            app.topics_next(app.page_size);
+        }
+        KeyCode::PageUp => {
+            app.topics_prev(app.page_size);
+        }
+        KeyCode::Char('b') if app.topic_open.is_some() => {
+            app.toggle_bookmark().await?;
+        }
+        KeyCode::Enter => {
+            if app.topic_open.is_some() {
+                app.open_detail().await?;
+            } else {
+                app.open_topic();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_stats_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.view = View::List;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.stats_next(1);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.stats_prev(1);
+        }
+        KeyCode::PageDown => {
+            app.stats_next(app.page_size);
+        }
+        KeyCode::PageUp => {
+            app.stats_prev(app.page_size);
+        }
+        KeyCode::Tab => {
+            app.cycle_stats_group();
+        }
+        _ => {}
+    }
+}
+
 async fn download_db(url: &str, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let response = reqwest::get(url).await?;
     let total_size = response.content_length().ok_or("Failed to get content length")?;
@@ -664,6 +1218,28 @@ async fn download_db(url: &str, output: &Path) -> Result<(), Box<dyn std::error:
     Ok(())
 }
 
+/// Show the password overlay until a password is submitted; `None` if the user cancels.
+fn prompt_password(
+    terminal: &mut ratatui::DefaultTerminal,
+    overlay: &mut ui::password_overlay::PasswordInputOverlay,
+) -> std::io::Result<Option<String>> {
+    loop {
+        terminal.draw(|frame| overlay.render(frame))?;
+        if !crossterm::event::poll(std::time::Duration::from_millis(100))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match overlay.handle_key(key) {
+                Some(ui::password_overlay::PasswordInputResult::Submit(password)) => return Ok(Some(password)),
+                Some(ui::password_overlay::PasswordInputResult::Cancel) => return Ok(None),
+                None => {}
+            }
+        }
+    }
+}
+
 fn read_password_with_stars() -> Result<String, Box<dyn std::error::Error>> {
     let mut password = String::new();
     enable_raw_mode()?;
@@ -718,7 +1294,7 @@ async fn handle_filters_key(app: &mut crate::app::App, key: event::KeyEvent) ->
                 }
                 KeyCode::Char('a') => {
                     app.filter_builder_state = FilterBuilderState::SelectingField;
-                    app.status_msg = "Select field: (c)ost, (i)nput, (o)utput, (m)odel, (h)ost".to_string();
+                    app.status_msg = "Select field: (c)ost, (i)nput, (o)utput, (m)odel, (h)ost, (t)ag, (b)ookmarked".to_string();
                 }
                 KeyCode::Char('d') => {
                     app.clear_global_filters();
@@ -749,6 +1325,15 @@ async fn handle_filters_key(app: &mut crate::app::App, key: event::KeyEvent) ->
                     app.filter_builder_state = FilterBuilderState::EnteringValue { field: "host".to_string(), step: 0, buffer: String::new(), min_val: 0.0 };
                     app.status_msg = "Enter host pattern (supports *):".to_string();
                 }
+                KeyCode::Char('t') => {
+                    app.filter_builder_state = FilterBuilderState::EnteringValue { field: "tag".to_string(), step: 0, buffer: String::new(), min_val: 0.0 };
+                    app.status_msg = format!("Enter tag pattern (supports *), known: {}", app.all_tags().join(", "));
+                }
+                KeyCode::Char('b') => {
+                    app.add_filter(crate::app::Filter::Match { field: "bookmarked".to_string(), pattern: "yes".to_string() });
+                    app.filter_builder_state = FilterBuilderState::Inactive;
+                    app.status_msg = "Added filter: bookmarked only".to_string();
+                }
                 KeyCode::Esc => {
                     app.filter_builder_state = FilterBuilderState::Inactive;
                     app.status_msg = String::new();
@@ -776,7 +1361,7 @@ async fn handle_filters_key(app: &mut crate::app::App, key: event::KeyEvent) ->
                                 app.status_msg = format!("Added filter: {} in range [{}, {}]", field, min_val, max);
                             }
                         }
-                        "model" | "host" => {
+                        "model" | "host" | "tag" => {
                             let pattern = if buffer.is_empty() { "*".to_string() } else { buffer };
                             app.add_filter(crate::app::Filter::Match { field: field.clone(), pattern: pattern.clone() });
                             app.filter_builder_state = FilterBuilderState::Inactive;
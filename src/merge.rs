@@ -0,0 +1,175 @@
+//! Combining the `items` rows of several databases into one.
+//!
+//! Rows are copied as raw values of the logical columns, so legacy inputs
+//! come out in the current layout. Entries for the same video and model are
+//! kept once, and identifiers are only changed where two inputs collide.
+
+use crate::duplicates::canonical_link;
+use crate::import::{checkpoint, CREATE_ITEMS_TABLE};
+use crate::schema;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use turso::Value;
+
+/// Counts reported after a merge.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub rows_read: usize,
+    /// Rows dropped because an entry with the same link and model was kept.
+    pub duplicates: usize,
+    /// Kept rows that got a new identifier because another input used theirs.
+    pub renumbered: usize,
+}
+
+fn column(name: &str) -> usize {
+    schema::column_names()
+        .position(|c| c == name)
+        .expect("logical column")
+}
+
+fn text<'a>(row: &'a [Value], name: &str) -> &'a str {
+    match &row[column(name)] {
+        Value::Text(s) => s,
+        _ => "",
+    }
+}
+
+/// Union of the rows of each input (as returned by `Database::dump_rows`), in input order.
+///
+/// Rows with the same canonical source link and model are duplicates; the one
+/// whose summary finished last wins, the earlier input on ties. Rows without a
+/// link are always kept. Identifiers of earlier inputs are kept, so annotations
+/// stay attached: a winning duplicate takes over the identifier of the entry it
+/// replaces, and a row whose identifier an earlier input already uses gets one
+/// after the highest identifier in use.
+pub fn merge_rows(inputs: Vec<Vec<Vec<Value>>>) -> (Vec<Vec<Value>>, MergeSummary) {
+    let id = column("identifier");
+    let mut summary = MergeSummary::default();
+    // Each kept row with the (input, row) position its identifier came from.
+    let mut kept: Vec<((usize, usize), Vec<Value>)> = Vec::new();
+    let mut by_key: HashMap<(String, String), usize> = HashMap::new();
+
+    for (input, rows) in inputs.into_iter().enumerate() {
+        for (position, mut row) in rows.into_iter().enumerate() {
+            summary.rows_read += 1;
+            let origin = (input, position);
+            let Some(link) = canonical_link(text(&row, "original_source_link")) else {
+                kept.push((origin, row));
+                continue;
+            };
+            match by_key.entry((link, text(&row, "model").to_string())) {
+                Entry::Occupied(entry) => {
+                    summary.duplicates += 1;
+                    let (_, existing) = &mut kept[*entry.get()];
+                    if text(&row, "summary_timestamp_end") > text(existing, "summary_timestamp_end") {
+                        row[id] = existing[id].clone();
+                        *existing = row;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(kept.len());
+                    kept.push((origin, row));
+                }
+            }
+        }
+    }
+
+    let identifier = |row: &[Value]| match row[id] {
+        Value::Integer(n) => Some(n),
+        _ => None,
+    };
+    let mut next_id = kept.iter().filter_map(|(_, r)| identifier(r)).max().unwrap_or(0) + 1;
+    let mut used = HashSet::new();
+    // Earlier inputs claim their identifiers first.
+    let mut by_precedence: Vec<usize> = (0..kept.len()).collect();
+    by_precedence.sort_by_key(|&i| kept[i].0);
+    for i in by_precedence {
+        let row = &mut kept[i].1;
+        if identifier(row).is_none_or(|n| !used.insert(n)) {
+            row[id] = Value::Integer(next_id);
+            used.insert(next_id);
+            next_id += 1;
+            summary.renumbered += 1;
+        }
+    }
+    (kept.into_iter().map(|(_, row)| row).collect(), summary)
+}
+
+/// Write rows in `schema::column_names()` order to a new database at `path`,
+/// checkpointed so the file is complete on its own.
+pub async fn write_rows(path: &Path, rows: Vec<Vec<Value>>) -> turso::Result<()> {
+    let db = turso::Builder::new_local(&path.to_string_lossy()).build().await?;
+    let conn = db.connect()?;
+    conn.execute(CREATE_ITEMS_TABLE, ()).await?;
+
+    let names: Vec<&str> = schema::column_names().collect();
+    let placeholders: Vec<String> = (1..=names.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!("INSERT INTO items ({}) VALUES ({})", names.join(", "), placeholders.join(", "));
+    conn.execute("BEGIN", ()).await?;
+    for row in rows {
+        conn.execute(&sql, turso::params::Params::Positional(row)).await?;
+    }
+    conn.execute("COMMIT", ()).await?;
+    checkpoint(&conn).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn row(id: i64, link: &str, model: &str, end: &str) -> Vec<Value> {
+        schema::column_names()
+            .map(|name| match name {
+                "identifier" => Value::Integer(id),
+                "original_source_link" => Value::Text(link.to_string()),
+                "model" => Value::Text(model.to_string()),
+                "summary_timestamp_end" => Value::Text(end.to_string()),
+                "summary" => Value::Text(format!("{} {}", id, end)),
+                "cost" => Value::Real(0.5),
+                "embedding" => Value::Null,
+                _ => Value::Text(String::new()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_rows_dedups_and_renumbers() {
+        let first = vec![
+            row(1, "https://www.youtube.com/watch?v=abc", "pro", "2025-01-01"),
+            row(2, "https://example.com/talk", "pro", "2025-01-01"),
+        ];
+        let second = vec![
+            // Same video and model, newer summary: replaces row 1 but keeps its
+            // identifier instead of taking row 2's
+            row(2, "https://youtu.be/abc", "pro", "2025-03-01"),
+            // Same video, other model: kept
+            row(8, "https://youtu.be/abc", "flash", "2025-01-01"),
+            // Identifier collision with the first input
+            row(8, "https://example.com/other", "pro", "2025-01-01"),
+            row(9, "", "pro", "2025-01-01"),
+        ];
+        let (rows, summary) = merge_rows(vec![first, second]);
+        assert_eq!(summary, MergeSummary { rows_read: 6, duplicates: 1, renumbered: 1 });
+
+        let ids: Vec<Value> = rows.iter().map(|r| r[column("identifier")].clone()).collect();
+        assert_eq!(ids, [1, 2, 8, 10, 9].map(Value::Integer));
+        assert_eq!(text(&rows[0], "summary"), "2 2025-03-01");
+    }
+
+    #[tokio::test]
+    async fn test_write_rows_reads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("merged.db");
+        let (rows, _) = merge_rows(vec![vec![row(3, "https://a", "m", "x")], vec![row(3, "https://b", "m", "y")]]);
+        write_rows(&path, rows).await.unwrap();
+
+        let db = Database::open(&path).await.unwrap();
+        assert!(db.schema().warnings().is_empty());
+        let dumped = db.dump_rows().await.unwrap();
+        assert_eq!(dumped.len(), 2);
+        assert_eq!(dumped[1][column("identifier")], Value::Integer(4));
+        assert_eq!(text(&dumped[1], "original_source_link"), "https://b");
+    }
+}
@@ -0,0 +1,162 @@
+//! Selection of list rows shared by the TUI and the headless subcommands:
+//! the free-text filter and a textual form of `Filter` expressions.
+
+use crate::app::Filter;
+use crate::db::TranscriptListItem;
+
+/// Fields compared numerically by `field=min..max`.
+const RANGE_FIELDS: [&str; 3] = ["cost", "input_tokens", "output_tokens"];
+/// Fields matched with wildcards by `field=pattern`.
+const MATCH_FIELDS: [&str; 5] = ["model", "host", "link", "tag", "bookmarked"];
+
+/// Whether `item` matches the list's free-text filter; `needle` must be lowercase.
+pub fn matches_text(item: &TranscriptListItem, needle: &str) -> bool {
+    needle.is_empty()
+        || item.summary.to_lowercase().contains(needle)
+        || item.host.to_lowercase().contains(needle)
+        || item.original_source_link.to_lowercase().contains(needle)
+}
+
+/// Items matching both the free-text filter and the optional filter expression.
+pub fn select<'a>(
+    items: &'a [TranscriptListItem],
+    text: &str,
+    filter: Option<&Filter>,
+) -> Vec<&'a TranscriptListItem> {
+    let needle = text.to_lowercase();
+    items
+        .iter()
+        .filter(|item| matches_text(item, &needle) && filter.is_none_or(|f| f.matches(item)))
+        .collect()
+}
+
+/// Split rows into runs of consecutive entries with identical summaries,
+/// which is how the list collapses re-runs of the same video.
+pub fn group_consecutive<'a>(
+    items: impl IntoIterator<Item = &'a TranscriptListItem>,
+) -> Vec<Vec<&'a TranscriptListItem>> {
+    let mut groups: Vec<Vec<&TranscriptListItem>> = Vec::new();
+    for item in items {
+        match groups.last_mut() {
+            Some(group) if group[0].summary == item.summary => group.push(item),
+            _ => groups.push(vec![item]),
+        }
+    }
+    groups
+}
+
+/// Parse a filter expression.
+///
+/// * `cost=0.01..0.5`, `output_tokens=1000..` and `input_tokens=..500` select a range
+///   (open ends are unbounded);
+/// * `model=gemini*`, `host=…`, `link=…`, `tag=…` and `bookmarked=yes` match
+///   case-insensitive wildcards;
+/// * `a|b` matches either side, `!a` negates.
+pub fn parse_filter(expr: &str) -> Result<Filter, String> {
+    let alternatives: Vec<&str> = expr.split('|').collect();
+    if alternatives.len() > 1 {
+        return alternatives
+            .into_iter()
+            .map(parse_filter)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Filter::Or);
+    }
+    let expr = expr.trim();
+    if let Some(rest) = expr.strip_prefix('!') {
+        return Ok(Filter::Not(Box::new(parse_filter(rest)?)));
+    }
+
+    let (field, value) = expr
+        .split_once('=')
+        .ok_or_else(|| format!("expected field=value, got \"{}\"", expr))?;
+    let field = field.trim().to_lowercase();
+    let value = value.trim();
+    if RANGE_FIELDS.contains(&field.as_str()) {
+        let (min, max) = value.split_once("..").unwrap_or((value, value));
+        let bound = |s: &str, open: f64| -> Result<f64, String> {
+            if s.trim().is_empty() {
+                Ok(open)
+            } else {
+                s.trim().parse().map_err(|_| format!("invalid number \"{}\" for {}", s, field))
+            }
+        };
+        Ok(Filter::Range {
+            min: bound(min, f64::NEG_INFINITY)?,
+            max: bound(max, f64::INFINITY)?,
+            field,
+        })
+    } else if MATCH_FIELDS.contains(&field.as_str()) {
+        Ok(Filter::Match { field, pattern: value.to_string() })
+    } else {
+        Err(format!(
+            "unknown filter field \"{}\" (expected one of: {}, {})",
+            field,
+            RANGE_FIELDS.join(", "),
+            MATCH_FIELDS.join(", ")
+        ))
+    }
+}
+
+/// Parse several expressions and combine them with AND, like filters added in the TUI.
+pub fn parse_filters(exprs: &[String]) -> Result<Option<Filter>, String> {
+    let mut filters: Vec<Filter> = exprs.iter().map(|e| parse_filter(e)).collect::<Result<_, _>>()?;
+    Ok(match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => Some(Filter::And(filters)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter_ranges_and_matches() {
+        assert_eq!(
+            parse_filter("cost=0.01..0.5").unwrap(),
+            Filter::Range { field: "cost".into(), min: 0.01, max: 0.5 }
+        );
+        assert_eq!(
+            parse_filter("output_tokens=1000..").unwrap(),
+            Filter::Range { field: "output_tokens".into(), min: 1000.0, max: f64::INFINITY }
+        );
+        assert_eq!(
+            parse_filter(" Model = gemini* ").unwrap(),
+            Filter::Match { field: "model".into(), pattern: "gemini*".into() }
+        );
+        assert_eq!(
+            parse_filter("!tag=ai|bookmarked=yes").unwrap(),
+            Filter::Or(vec![
+                Filter::Not(Box::new(Filter::Match { field: "tag".into(), pattern: "ai".into() })),
+                Filter::Match { field: "bookmarked".into(), pattern: "yes".into() },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_group_consecutive_identical_summaries() {
+        let item = |identifier: i64, summary: &str| TranscriptListItem {
+            summary: summary.to_string(),
+            ..TranscriptListItem::fixture(identifier)
+        };
+        let items = [item(1, "a"), item(2, "a"), item(3, "b"), item(4, "a")];
+        let ids: Vec<Vec<i64>> = group_consecutive(&items)
+            .iter()
+            .map(|g| g.iter().map(|i| i.identifier).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![1, 2], vec![3], vec![4]]);
+    }
+
+    #[test]
+    fn test_parse_filter_errors() {
+        assert!(parse_filter("cost").is_err());
+        assert!(parse_filter("cost=cheap").is_err());
+        assert!(parse_filter("colour=red").is_err());
+        assert_eq!(parse_filters(&[]).unwrap(), None);
+        assert!(matches!(
+            parse_filters(&["cost=..1".into(), "model=x".into()]).unwrap(),
+            Some(Filter::And(f)) if f.len() == 2
+        ));
+    }
+}
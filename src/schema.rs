@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Table names the explorer knows how to read, in order of preference.
+pub const TABLE_NAMES: [&str; 2] = ["items", "summaries"];
+
+/// A logical column of the `items` table as used by the queries in `db.rs`.
+struct ColumnSpec {
+    name: &'static str,
+    required: bool,
+    /// Column names used by older pipeline versions for the same data.
+    aliases: &'static [&'static str],
+    /// SQL literal substituted when the column is absent.
+    default: &'static str,
+}
+
+const COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec { name: "identifier", required: true, aliases: &["id"], default: "0" },
+    ColumnSpec { name: "summary", required: true, aliases: &["summary_text", "abstract"], default: "''" },
+    ColumnSpec { name: "model", required: false, aliases: &["summary_model"], default: "''" },
+    ColumnSpec { name: "host", required: false, aliases: &["client_host"], default: "''" },
+    ColumnSpec { name: "original_source_link", required: false, aliases: &["source_link", "link", "url"], default: "''" },
+    ColumnSpec { name: "output_language", required: false, aliases: &["language", "lang"], default: "''" },
+    ColumnSpec { name: "summary_done", required: false, aliases: &["done"], default: "0" },
+    ColumnSpec { name: "summary_input_tokens", required: false, aliases: &["input_tokens"], default: "0" },
+    ColumnSpec { name: "summary_output_tokens", required: false, aliases: &["output_tokens"], default: "0" },
+    ColumnSpec { name: "transcript", required: false, aliases: &["transcript_text"], default: "''" },
+    ColumnSpec { name: "timestamps", required: false, aliases: &[], default: "''" },
+    ColumnSpec { name: "timestamped_summary_in_youtube_format", required: false, aliases: &["youtube_timestamps"], default: "''" },
+    ColumnSpec { name: "cost", required: false, aliases: &["summary_cost", "price"], default: "0" },
+    ColumnSpec { name: "embedding", required: false, aliases: &["summary_embedding"], default: "NULL" },
+    ColumnSpec { name: "embedding_model", required: false, aliases: &[], default: "''" },
+    ColumnSpec { name: "summary_timestamp_start", required: false, aliases: &["started_at"], default: "''" },
+    ColumnSpec { name: "summary_timestamp_end", required: false, aliases: &["finished_at"], default: "''" },
+];
+
+/// Names of all logical columns, in a fixed order.
+pub fn column_names() -> impl Iterator<Item = &'static str> {
+    COLUMNS.iter().map(|c| c.name)
+}
+
+/// Reasons a database cannot be opened by the explorer.
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("no transcript table found (expected one of: {expected}; found: {found})")]
+    MissingTable { expected: String, found: String },
+
+    #[error("table '{table}' is missing required column(s): {missing} (found: {found})")]
+    MissingColumns { table: String, missing: String, found: String },
+}
+
+/// Column mapping for one database, resolved once in `Database::open`.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub table: String,
+    /// Logical column -> actual column name, `None` if absent.
+    columns: HashMap<&'static str, Option<String>>,
+    /// Optional logical columns that are absent.
+    pub missing_optional: Vec<&'static str>,
+    /// Legacy columns mapped onto logical ones, as `(actual, logical)`.
+    pub legacy: Vec<(String, &'static str)>,
+}
+
+impl Schema {
+    /// Pick the transcript table from a list of table names.
+    pub fn find_table(tables: &[String]) -> Result<String, SchemaError> {
+        TABLE_NAMES
+            .iter()
+            .find_map(|want| tables.iter().find(|t| t.eq_ignore_ascii_case(want)))
+            .cloned()
+            .ok_or_else(|| SchemaError::MissingTable {
+                expected: TABLE_NAMES.join(", "),
+                found: if tables.is_empty() { "none".to_string() } else { tables.join(", ") },
+            })
+    }
+
+    /// Resolve logical columns against the columns actually present in `table`.
+    pub fn resolve(table: &str, present: &[String]) -> Result<Self, SchemaError> {
+        let find = |name: &str| present.iter().find(|c| c.eq_ignore_ascii_case(name)).cloned();
+
+        let mut columns = HashMap::new();
+        let mut missing_required = Vec::new();
+        let mut missing_optional = Vec::new();
+        let mut legacy = Vec::new();
+
+        for spec in COLUMNS {
+            let actual = find(spec.name).or_else(|| {
+                spec.aliases.iter().find_map(|alias| find(alias)).inspect(|actual| {
+                    legacy.push((actual.clone(), spec.name));
+                })
+            });
+            if actual.is_none() {
+                if spec.required {
+                    missing_required.push(spec.name);
+                } else {
+                    missing_optional.push(spec.name);
+                }
+            }
+            columns.insert(spec.name, actual);
+        }
+
+        if !missing_required.is_empty() {
+            return Err(SchemaError::MissingColumns {
+                table: table.to_string(),
+                missing: missing_required.join(", "),
+                found: present.join(", "),
+            });
+        }
+
+        Ok(Schema {
+            table: table.to_string(),
+            columns,
+            missing_optional,
+            legacy,
+        })
+    }
+
+    /// SQL expression for a logical column: the quoted actual column, or its default literal.
+    pub fn col(&self, name: &str) -> String {
+        match self.columns.get(name) {
+            Some(Some(actual)) => quote_ident(actual),
+            _ => COLUMNS
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.default.to_string())
+                .unwrap_or_else(|| "NULL".to_string()),
+        }
+    }
+
+    /// Like `col`, but qualified with a table alias (`t."summary"`).
+    pub fn col_of(&self, alias: &str, name: &str) -> String {
+        match self.columns.get(name) {
+            Some(Some(actual)) => format!("{}.{}", alias, quote_ident(actual)),
+            _ => self.col(name),
+        }
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        matches!(self.columns.get(name), Some(Some(_)))
+    }
+
+    /// Quoted table name for use in SQL.
+    pub fn table_sql(&self) -> String {
+        quote_ident(&self.table)
+    }
+
+    /// Similarity search needs the embedding column.
+    pub fn has_embeddings(&self) -> bool {
+        self.has("embedding")
+    }
+
+    /// Short human-readable notes about degraded features, empty for a current schema.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if !self.has_embeddings() {
+            notes.push("no embedding column: similarity search disabled".to_string());
+        }
+        let other_missing: Vec<&str> = self
+            .missing_optional
+            .iter()
+            .copied()
+            .filter(|c| *c != "embedding")
+            .collect();
+        if !other_missing.is_empty() {
+            notes.push(format!("missing optional column(s): {}", other_missing.join(", ")));
+        }
+        if !self.legacy.is_empty() {
+            let mapped: Vec<String> = self
+                .legacy
+                .iter()
+                .map(|(actual, logical)| format!("{} -> {}", actual, logical))
+                .collect();
+            notes.push(format!("legacy columns mapped: {}", mapped.join(", ")));
+        }
+        notes
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_current_schema() {
+        let present = cols(&[
+            "identifier", "model", "host", "original_source_link", "output_language", "summary",
+            "summary_done", "summary_input_tokens", "summary_output_tokens", "transcript",
+            "timestamps", "timestamped_summary_in_youtube_format", "cost", "embedding",
+            "embedding_model", "summary_timestamp_start", "summary_timestamp_end",
+        ]);
+        let schema = Schema::resolve("items", &present).unwrap();
+        assert!(schema.warnings().is_empty());
+        assert_eq!(schema.col("summary"), "\"summary\"");
+        assert_eq!(schema.col_of("t", "cost"), "t.\"cost\"");
+    }
+
+    #[test]
+    fn test_resolve_missing_required() {
+        let err = Schema::resolve("items", &cols(&["identifier", "host"])).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("summary"));
+        assert!(msg.contains("found: identifier, host"));
+    }
+
+    #[test]
+    fn test_resolve_legacy_and_defaults() {
+        let schema = Schema::resolve("items", &cols(&["id", "summary", "link"])).unwrap();
+        assert_eq!(schema.col("identifier"), "\"id\"");
+        assert_eq!(schema.col("original_source_link"), "\"link\"");
+        assert_eq!(schema.col("cost"), "0");
+        assert_eq!(schema.col_of("t", "embedding"), "NULL");
+        assert!(!schema.has_embeddings());
+        let warnings = schema.warnings().join("; ");
+        assert!(warnings.contains("similarity search disabled"));
+        assert!(warnings.contains("id -> identifier"));
+    }
+
+    #[test]
+    fn test_find_table() {
+        assert_eq!(Schema::find_table(&cols(&["meta", "items"])).unwrap(), "items");
+        assert_eq!(Schema::find_table(&cols(&["summaries"])).unwrap(), "summaries");
+        assert!(Schema::find_table(&cols(&["other"])).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::db::{SimilarityQuery, SIMILARITY_DIMS};
+use crate::vector::Metric;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Embedding prefix lengths offered in the Similar view; `None` means full length.
+pub const DIMS_CHOICES: [Option<usize>; 4] = [Some(256), Some(512), Some(SIMILARITY_DIMS), None];
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Failed to read settings: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse settings: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// User preferences, stored as `settings.json` in the config directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub similarity: SimilaritySettings,
+    pub database: DatabaseSettings,
+}
+
+/// How database files are opened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    /// Decrypt encrypted databases into memory instead of a temporary file,
+    /// as `--in-memory` does; needs RAM for about twice the decrypted size.
+    pub decrypt_in_memory: bool,
+    /// Passphrases the TUI accepts before giving up on an encrypted database.
+    pub password_attempts: u32,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self { decrypt_in_memory: false, password_attempts: 3 }
+    }
+}
+
+/// How the Similar view searches for neighbours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimilaritySettings {
+    pub metric: Metric,
+    /// Embedding prefix length, one of `DIMS_CHOICES`.
+    pub dims: Option<usize>,
+    /// Cut-off distance; results farther away are not shown.
+    pub max_distance: Option<f64>,
+    /// Results fetched per page ("load more" fetches another page).
+    pub page_size: usize,
+    /// Take candidates from the approximate nearest-neighbour index once it is ready.
+    pub use_index: bool,
+    /// Fetch a larger candidate pool from the index and re-rank it exactly;
+    /// without it only as many candidates as results are requested.
+    pub exact_rerank: bool,
+}
+
+impl Default for SimilaritySettings {
+    fn default() -> Self {
+        Self {
+            metric: Metric::Cosine,
+            dims: Some(SIMILARITY_DIMS),
+            max_distance: None,
+            page_size: 20,
+            use_index: true,
+            exact_rerank: true,
+        }
+    }
+}
+
+impl SimilaritySettings {
+    /// Query for the page starting at `offset`.
+    pub fn query(&self, offset: usize) -> SimilarityQuery {
+        SimilarityQuery {
+            metric: self.metric,
+            dims: self.dims,
+            max_distance: self.max_distance,
+            limit: self.page_size as i64,
+            offset: offset as i64,
+            candidates: None,
+        }
+    }
+
+    /// Switch to the next metric. The cut-off is cleared since distances
+    /// of different metrics are not comparable.
+    pub fn cycle_metric(&mut self) {
+        self.metric = self.metric.next();
+        self.max_distance = None;
+    }
+
+    pub fn cycle_dims(&mut self) {
+        let i = DIMS_CHOICES.iter().position(|d| *d == self.dims).unwrap_or(0);
+        self.dims = DIMS_CHOICES[(i + 1) % DIMS_CHOICES.len()];
+    }
+
+    pub fn dims_label(&self) -> String {
+        match self.dims {
+            Some(d) => d.to_string(),
+            None => "full".to_string(),
+        }
+    }
+
+    /// Replace values a hand-edited file may have broken.
+    fn sanitize(&mut self) {
+        if !DIMS_CHOICES.contains(&self.dims) {
+            self.dims = Some(SIMILARITY_DIMS);
+        }
+        if self.max_distance.is_some_and(|d| !self.metric.valid_cutoff(d)) {
+            self.max_distance = None;
+        }
+        if self.page_size == 0 {
+            self.page_size = Self::default().page_size;
+        }
+    }
+}
+
+impl Settings {
+    /// Default location: `<config dir>/settings.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("com", "rocketrecap", "transcript-explorer")
+            .map(|dirs| dirs.config_dir().join("settings.json"))
+    }
+
+    /// Load settings from `path`; a missing file yields the defaults.
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let mut settings: Self = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        settings.similarity.sanitize();
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip_and_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("settings.json");
+        assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+
+        let mut settings = Settings::default();
+        settings.similarity.metric = Metric::Dot;
+        settings.similarity.dims = None;
+        settings.similarity.max_distance = Some(0.25);
+        settings.database.decrypt_in_memory = true;
+        settings.database.password_attempts = 5;
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_settings_sanitize_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"similarity": {"dims": 1000, "page_size": 0, "max_distance": -0.5}}"#).unwrap();
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.similarity.dims, Some(SIMILARITY_DIMS));
+        assert_eq!(settings.similarity.page_size, 20);
+        assert_eq!(settings.similarity.max_distance, None);
+        assert_eq!(settings.similarity.metric, Metric::Cosine);
+    }
+
+    #[test]
+    fn test_cycle_metric_clears_cutoff() {
+        let mut s = SimilaritySettings { max_distance: Some(0.2), ..Default::default() };
+        s.cycle_metric();
+        assert_eq!(s.metric, Metric::L2);
+        assert_eq!(s.max_distance, None);
+        s.cycle_dims();
+        assert_eq!(s.dims, None);
+        s.cycle_dims();
+        assert_eq!(s.dims, Some(256));
+    }
+}
@@ -0,0 +1,374 @@
+//! A minimal reader for the SQLite file format, enough to check a database
+//! image held in memory, count the rows of its tables and read them back
+//! without writing the image to a file.
+//!
+//! Only table b-trees are walked; see <https://www.sqlite.org/fileformat2.html>.
+
+use std::collections::HashSet;
+use turso::Value;
+
+/// The first 16 bytes of every SQLite database file.
+pub const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+const LEAF_TABLE: u8 = 0x0D;
+const INTERIOR_TABLE: u8 = 0x05;
+
+/// Fields of the 100-byte database header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub page_size: usize,
+    /// Database size in pages as recorded in the header.
+    pub page_count: u32,
+    /// Bytes at the end of each page reserved for extensions.
+    pub reserved: usize,
+}
+
+/// A table or index listed in `sqlite_master`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaEntry {
+    /// `table`, `index`, `view` or `trigger`.
+    pub kind: String,
+    pub name: String,
+    /// Table an index or trigger belongs to; a table's own name.
+    pub table: String,
+    /// 0 for views, triggers and virtual tables.
+    pub root_page: u32,
+    /// `CREATE` statement; empty for automatic indexes.
+    pub sql: String,
+}
+
+impl SchemaEntry {
+    /// A table declared `WITHOUT ROWID`, which is stored as an index b-tree
+    /// and cannot be read back with `rows`.
+    pub fn is_without_rowid(&self) -> bool {
+        let options = self.sql.rsplit_once(')').map_or("", |(_, rest)| rest).to_ascii_uppercase();
+        let words: Vec<&str> = options
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .collect();
+        self.kind == "table" && words.windows(2).any(|w| w == ["WITHOUT", "ROWID"])
+    }
+}
+
+fn be_u16(b: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?) as usize)
+}
+
+fn be_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// SQLite varint at `at`: value and length.
+fn varint(b: &[u8], at: usize) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let byte = *b.get(at + i)?;
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Parse and check the database header.
+pub fn parse_header(db: &[u8]) -> Result<Header, String> {
+    if db.len() < 100 || &db[..16] != SQLITE_MAGIC {
+        return Err("no SQLite header".to_string());
+    }
+    let page_size = match be_u16(db, 16).unwrap_or(0) {
+        1 => 65536,
+        n if n >= 512 && n.is_power_of_two() => n,
+        n => return Err(format!("invalid page size {}", n)),
+    };
+    Ok(Header {
+        page_size,
+        page_count: be_u32(db, 28).unwrap_or(0),
+        reserved: db[20] as usize,
+    })
+}
+
+struct Reader<'a> {
+    db: &'a [u8],
+    header: Header,
+}
+
+impl<'a> Reader<'a> {
+    fn page(&self, number: u32) -> Result<&'a [u8], String> {
+        let start = (number as usize)
+            .checked_sub(1)
+            .map(|n| n * self.header.page_size)
+            .ok_or("page number 0")?;
+        self.db
+            .get(start..start + self.header.page_size)
+            .ok_or_else(|| format!("page {} is past the end of the file", number))
+    }
+
+    fn usable(&self) -> usize {
+        self.header.page_size - self.header.reserved
+    }
+
+    /// Visit every leaf cell of the table b-tree rooted at `root` as `(page, cell offset)`.
+    fn walk(&self, root: u32, visit: &mut dyn FnMut(&'a [u8], usize) -> Result<(), String>) -> Result<(), String> {
+        let mut stack = vec![root];
+        let mut visited = 0usize;
+        while let Some(number) = stack.pop() {
+            visited += 1;
+            if visited > self.db.len() / self.header.page_size + 1 {
+                return Err("b-tree has a cycle".to_string());
+            }
+            let page = self.page(number)?;
+            // Page 1 starts with the database header
+            let at = if number == 1 { 100 } else { 0 };
+            let cells = be_u16(page, at + 3).ok_or("truncated page header")?;
+            let kind = page[at];
+            let header_len = match kind {
+                LEAF_TABLE => 8,
+                INTERIOR_TABLE => 12,
+                other => return Err(format!("page {} has unexpected type {:#04x}", number, other)),
+            };
+            let mut children = Vec::new();
+            for i in 0..cells {
+                let offset = be_u16(page, at + header_len + 2 * i).ok_or("truncated cell pointer array")?;
+                if kind == LEAF_TABLE {
+                    visit(page, offset)?;
+                } else {
+                    children.push(be_u32(page, offset).ok_or("truncated interior cell")?);
+                }
+            }
+            if kind == INTERIOR_TABLE {
+                children.push(be_u32(page, at + 8).ok_or("truncated page header")?);
+            }
+            // Reversed, so the leftmost child is visited next and rows come in rowid order
+            stack.extend(children.into_iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Rowid and payload of the leaf cell at `offset`, following overflow pages.
+    fn payload(&self, page: &[u8], offset: usize) -> Result<(i64, Vec<u8>), String> {
+        let (size, n) = varint(page, offset).ok_or("truncated cell")?;
+        let (rowid, m) = varint(page, offset + n).ok_or("truncated cell")?;
+        let start = offset + n + m;
+        // No payload can be larger than the pages of the image holding it
+        let size = usize::try_from(size)
+            .ok()
+            .filter(|&size| size <= self.db.len())
+            .ok_or_else(|| format!("payload of {} bytes is larger than the database", size))?;
+
+        let u = self.usable();
+        let max_local = u - 35;
+        let min_local = (u - 12) * 32 / 255 - 23;
+        let local = if size <= max_local {
+            size
+        } else {
+            let k = min_local + (size - min_local) % (u - 4);
+            if k <= max_local { k } else { min_local }
+        };
+        let mut out = page.get(start..start + local).ok_or("truncated cell payload")?.to_vec();
+        let mut next = if local < size { be_u32(page, start + local).ok_or("truncated overflow pointer")? } else { 0 };
+        let mut visited = HashSet::new();
+        while out.len() < size {
+            if !visited.insert(next) {
+                return Err(format!("overflow chain has a cycle at page {}", next));
+            }
+            let overflow = self.page(next)?;
+            let take = (size - out.len()).min(u - 4);
+            out.extend_from_slice(overflow.get(4..4 + take).ok_or("truncated overflow page")?);
+            next = be_u32(overflow, 0).unwrap_or(0);
+        }
+        Ok((rowid as i64, out))
+    }
+}
+
+/// Values of a record.
+fn record_values(record: &[u8]) -> Result<Vec<Value>, String> {
+    let (header_len, n) = varint(record, 0).ok_or("truncated record")?;
+    let mut types = Vec::new();
+    let mut at = n;
+    while at < header_len as usize {
+        let (t, n) = varint(record, at).ok_or("truncated record header")?;
+        types.push(t);
+        at += n;
+    }
+    let mut body = header_len as usize;
+    let mut values = Vec::with_capacity(types.len());
+    for t in types {
+        let len = match t {
+            0 | 8 | 9 => 0,
+            1..=4 => t as usize,
+            5 => 6,
+            6 | 7 => 8,
+            t if t >= 12 => ((t - 12) / 2) as usize,
+            _ => return Err(format!("invalid serial type {}", t)),
+        };
+        let bytes = record.get(body..body + len).ok_or("truncated record body")?;
+        values.push(match t {
+            0 => Value::Null,
+            1..=6 => {
+                let mut v = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+                for b in bytes {
+                    v = (v << 8) | *b as i64;
+                }
+                Value::Integer(v)
+            }
+            7 => Value::Real(f64::from_be_bytes(bytes.try_into().expect("8 bytes"))),
+            8 => Value::Integer(0),
+            9 => Value::Integer(1),
+            t if t % 2 == 1 => Value::Text(String::from_utf8_lossy(bytes).into_owned()),
+            _ => Value::Blob(bytes.to_vec()),
+        });
+        body += len;
+    }
+    Ok(values)
+}
+
+/// Entries of the schema table (`sqlite_master`, rooted at page 1).
+pub fn schema(db: &[u8]) -> Result<Vec<SchemaEntry>, String> {
+    let text = |v: &Value| match v {
+        Value::Text(s) => s.clone(),
+        _ => String::new(),
+    };
+    rows(db, 1)?
+        .map(|row| {
+            let (_, values) = row?;
+            let [kind, name, table, root, sql, ..] = &values[..] else {
+                return Err("invalid schema record".to_string());
+            };
+            let root_page = match root {
+                Value::Integer(n) => u32::try_from(*n).map_err(|_| format!("invalid root page for {}", text(name)))?,
+                _ => 0,
+            };
+            Ok(SchemaEntry { kind: text(kind), name: text(name), table: text(table), root_page, sql: text(sql) })
+        })
+        .collect()
+}
+
+/// Tables listed in the schema, without views and virtual tables.
+pub fn tables(db: &[u8]) -> Result<Vec<SchemaEntry>, String> {
+    Ok(schema(db)?
+        .into_iter()
+        .filter(|e| e.kind == "table" && e.root_page != 0)
+        .collect())
+}
+
+/// Rows of the table b-tree rooted at `root_page` as rowid and values, in rowid order.
+///
+/// Columns added with `ALTER TABLE` after a row was written are missing from
+/// its values; a rowid alias (`INTEGER PRIMARY KEY`) column reads as NULL.
+pub fn rows(db: &[u8], root_page: u32) -> Result<impl Iterator<Item = Result<(i64, Vec<Value>), String>> + '_, String> {
+    let reader = Reader { db, header: parse_header(db)? };
+    let mut cells = Vec::new();
+    reader.walk(root_page, &mut |page, offset| {
+        cells.push((page, offset));
+        Ok(())
+    })?;
+    Ok(cells.into_iter().map(move |(page, offset)| {
+        let (rowid, record) = reader.payload(page, offset)?;
+        Ok((rowid, record_values(&record)?))
+    }))
+}
+
+/// Number of rows in the table b-tree rooted at `root_page`.
+pub fn row_count(db: &[u8], root_page: u32) -> Result<u64, String> {
+    let reader = Reader { db, header: parse_header(db)? };
+    let mut rows = 0u64;
+    reader.walk(root_page, &mut |_, _| {
+        rows += 1;
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tables_and_row_counts_match_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("t.db");
+        let conn = turso::Builder::new_local(&path.to_string_lossy())
+            .build()
+            .await
+            .unwrap()
+            .connect()
+            .unwrap();
+        // A long CREATE statement spills into an overflow page, the rows into several pages
+        let long_name = "c".repeat(5000);
+        conn.execute(&format!("CREATE TABLE items (identifier INTEGER PRIMARY KEY, summary TEXT, {} TEXT)", long_name), ())
+            .await
+            .unwrap();
+        conn.execute("CREATE TABLE other (x)", ()).await.unwrap();
+        for i in 0..300 {
+            conn.execute("INSERT INTO items (identifier, summary) VALUES (?1, ?2)", (i, "x".repeat(200)))
+                .await
+                .unwrap();
+        }
+        crate::import::checkpoint(&conn).await.unwrap();
+        drop(conn);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let header = parse_header(&bytes).unwrap();
+        assert_eq!(bytes.len(), header.page_size * header.page_count as usize);
+        let listed = tables(&bytes).unwrap();
+        let names: Vec<&str> = listed.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["items", "other"]);
+        assert_eq!(row_count(&bytes, listed[0].root_page).unwrap(), 300);
+        assert_eq!(row_count(&bytes, listed[1].root_page).unwrap(), 0);
+
+        assert!(parse_header(b"not a database").is_err());
+        assert!(tables(&bytes[..header.page_size]).is_err());
+    }
+
+    /// Two 512-byte pages: page 1 holds a table leaf with one cell whose
+    /// header is `cell`, page 2 is an overflow page pointing to itself.
+    fn corrupt_image(cell: &[u8]) -> Vec<u8> {
+        let mut db = vec![0u8; 1024];
+        db[..16].copy_from_slice(SQLITE_MAGIC);
+        db[16..18].copy_from_slice(&512u16.to_be_bytes());
+        db[28..32].copy_from_slice(&2u32.to_be_bytes());
+        db[100] = LEAF_TABLE;
+        db[103..105].copy_from_slice(&1u16.to_be_bytes());
+        db[108..110].copy_from_slice(&400u16.to_be_bytes());
+        db[400..400 + cell.len()].copy_from_slice(cell);
+        db[512..516].copy_from_slice(&2u32.to_be_bytes());
+        db
+    }
+
+    #[test]
+    fn test_corrupt_payloads_are_rejected() {
+        // 1000 bytes: 39 local bytes, then the overflow pointer to page 2
+        let mut cell = vec![0x87, 0x68, 0x01];
+        cell.extend_from_slice(&[0u8; 39]);
+        cell.extend_from_slice(&2u32.to_be_bytes());
+        let err = rows(&corrupt_image(&cell), 1).unwrap().next().unwrap().unwrap_err();
+        assert!(err.contains("cycle"), "{}", err);
+
+        // 2^36 bytes in a 1 KB image
+        let err = rows(&corrupt_image(&[0x82, 0x80, 0x80, 0x80, 0x80, 0x00, 0x01]), 1)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(err.contains("larger than the database"), "{}", err);
+    }
+
+    #[test]
+    fn test_without_rowid_tables() {
+        let table = |sql: &str| SchemaEntry {
+            kind: "table".to_string(),
+            name: "t".to_string(),
+            table: "t".to_string(),
+            root_page: 2,
+            sql: sql.to_string(),
+        };
+        assert!(table("CREATE TABLE t (k TEXT PRIMARY KEY, v) WITHOUT ROWID").is_without_rowid());
+        assert!(table("create table t (k primary key) strict, without\n  rowid").is_without_rowid());
+        assert!(!table("CREATE TABLE t (without_rowid TEXT, \"WITHOUT ROWID\" TEXT)").is_without_rowid());
+        assert!(!table("CREATE TABLE t (k)").is_without_rowid());
+    }
+}
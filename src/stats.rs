@@ -0,0 +1,203 @@
+use crate::db::TranscriptListItem;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Numeric fields statistics are computed for.
+pub const FIELDS: [&str; 3] = ["cost", "input_tokens", "output_tokens"];
+
+/// Value of one of `FIELDS` for `item`.
+pub fn field_value(item: &TranscriptListItem, field: &str) -> f64 {
+    match field {
+        "cost" => item.cost,
+        "input_tokens" => item.summary_input_tokens as f64,
+        "output_tokens" => item.summary_output_tokens as f64,
+        _ => 0.0,
+    }
+}
+
+/// Summary statistics of one numeric field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub count: usize,
+    pub mean: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    pub mad: f64,
+    pub p5: f64,
+    pub p95: f64,
+}
+
+fn median_of_sorted(values: &[f64]) -> f64 {
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+impl Stats {
+    pub fn from_values(mut values: Vec<f64>) -> Stats {
+        let n = values.len();
+        if n == 0 {
+            return Stats::default();
+        }
+
+        let sum: f64 = values.iter().sum();
+        let mean = sum / n as f64;
+        let var_sum: f64 = values.iter().map(|&v| (v - mean).powi(2)).sum();
+        let stddev = (var_sum / n as f64).sqrt();
+
+        values.sort_by(f64::total_cmp);
+        let median = median_of_sorted(&values);
+        let mut devs: Vec<f64> = values.iter().map(|&v| (v - median).abs()).collect();
+        devs.sort_by(f64::total_cmp);
+
+        Stats {
+            count: n,
+            mean,
+            stddev,
+            min: values[0],
+            max: values[n - 1],
+            median,
+            mad: median_of_sorted(&devs),
+            p5: values[(n as f64 * 0.05) as usize],
+            p95: values[(n as f64 * 0.95).min(n as f64 - 1.0) as usize],
+        }
+    }
+}
+
+/// Dimension a breakdown groups entries by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Model,
+    Host,
+    /// Output language of the summary
+    Language,
+    /// Month the summary was made (`YYYY-MM` of its start timestamp)
+    Month,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 4] = [Self::Model, Self::Host, Self::Language, Self::Month];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Model => "model",
+            Self::Host => "host",
+            Self::Language => "language",
+            Self::Month => "month",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Model => Self::Host,
+            Self::Host => Self::Language,
+            Self::Language => Self::Month,
+            Self::Month => Self::Model,
+        }
+    }
+
+    /// Group key of `item`; empty values are reported as `(none)`.
+    fn key(self, item: &TranscriptListItem) -> String {
+        let key = match self {
+            Self::Model => item.model.trim(),
+            Self::Host => item.host.trim(),
+            Self::Language => item.output_language.trim(),
+            Self::Month => item
+                .summary_timestamp_start
+                .get(..7)
+                .filter(|m| m.as_bytes()[4] == b'-' && m[..4].bytes().all(|b| b.is_ascii_digit()))
+                .unwrap_or(""),
+        };
+        if key.is_empty() { "(none)".to_string() } else { key.to_string() }
+    }
+}
+
+/// Statistics of the entries sharing one group key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupStats {
+    pub key: String,
+    pub count: usize,
+    pub total_cost: f64,
+    pub cost: Stats,
+    pub input_tokens: Stats,
+    pub output_tokens: Stats,
+}
+
+/// Statistics per group of `items`, highest total cost first
+/// (months in chronological order instead).
+pub fn breakdown<'a>(items: impl IntoIterator<Item = &'a TranscriptListItem>, by: GroupBy) -> Vec<GroupStats> {
+    let mut groups: HashMap<String, Vec<&TranscriptListItem>> = HashMap::new();
+    for item in items {
+        groups.entry(by.key(item)).or_default().push(item);
+    }
+    let mut out: Vec<GroupStats> = groups
+        .into_iter()
+        .map(|(key, members)| {
+            let stats = |field: &str| Stats::from_values(members.iter().map(|i| field_value(i, field)).collect());
+            GroupStats {
+                key,
+                count: members.len(),
+                total_cost: members.iter().map(|i| i.cost).sum(),
+                cost: stats("cost"),
+                input_tokens: stats("input_tokens"),
+                output_tokens: stats("output_tokens"),
+            }
+        })
+        .collect();
+    match by {
+        GroupBy::Month => out.sort_by(|a, b| a.key.cmp(&b.key)),
+        _ => out.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost).then(a.key.cmp(&b.key))),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(model: &str, cost: f64, start: &str) -> TranscriptListItem {
+        TranscriptListItem {
+            cost,
+            model: model.to_string(),
+            output_language: "en".to_string(),
+            summary_input_tokens: 100,
+            summary_output_tokens: 10,
+            summary_timestamp_start: start.to_string(),
+            ..TranscriptListItem::fixture(0)
+        }
+    }
+
+    #[test]
+    fn test_stats_from_values() {
+        let s = Stats::from_values(vec![4.0, 1.0, 3.0, 2.0]);
+        assert_eq!((s.count, s.min, s.max, s.median, s.mean), (4, 1.0, 4.0, 2.5, 2.5));
+        assert_eq!(s.mad, 1.0);
+        assert_eq!(Stats::from_values(Vec::new()), Stats::default());
+    }
+
+    #[test]
+    fn test_breakdown_by_model_and_month() {
+        let items = [
+            item("a", 1.0, "2025-02-11T10:00:00"),
+            item("b", 5.0, "2025-01-03T10:00:00"),
+            item("a", 3.0, "2025-02-20T10:00:00"),
+            item("", 0.5, "garbage"),
+        ];
+        let by_model = breakdown(&items, GroupBy::Model);
+        let keys: Vec<&str> = by_model.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["b", "a", "(none)"]);
+        assert_eq!((by_model[1].count, by_model[1].total_cost, by_model[1].cost.mean), (2, 4.0, 2.0));
+
+        let by_month = breakdown(&items, GroupBy::Month);
+        let keys: Vec<&str> = by_month.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["(none)", "2025-01", "2025-02"]);
+        assert_eq!(breakdown(&items, GroupBy::Language)[0].count, 4);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::db::Database;
+use crate::vector;
+
+/// Embedding prefix length used for clustering; short prefixes keep k-means fast.
+pub const TOPIC_DIMS: usize = 256;
+/// Upper bound on Lloyd iterations.
+const MAX_ITERATIONS: usize = 25;
+/// Most topics offered, however large the corpus.
+const MAX_TOPICS: usize = 40;
+
+/// A group of transcripts about the same theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    /// Member identifiers, most representative (closest to the centroid) first.
+    pub members: Vec<i64>,
+    /// Mean cosine similarity of the members to the centroid.
+    pub cohesion: f32,
+}
+
+impl Topic {
+    /// Label built from the titles of the `n` most representative members.
+    pub fn label(&self, n: usize, title: impl Fn(i64) -> Option<String>) -> String {
+        let titles: Vec<String> = self
+            .members
+            .iter()
+            .filter_map(|id| title(*id))
+            .filter(|t| !t.is_empty())
+            .take(n)
+            .collect();
+        titles.join(" · ")
+    }
+}
+
+/// Number of topics for a corpus of `n` embeddings (rule of thumb `sqrt(n / 2)`).
+pub fn default_topic_count(n: usize) -> usize {
+    (((n / 2) as f64).sqrt().round() as usize).clamp(1, MAX_TOPICS).min(n)
+}
+
+/// Deterministic xorshift64* generator, so the same corpus gives the same topics.
+struct Rng(u64);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Spherical k-means: assign each (normalized) point to one of `k` clusters.
+///
+/// Seeds are picked with k-means++ and the loop stops once no point moves.
+/// Returns the cluster of every point and the final (normalized) centroids.
+fn kmeans(points: &[Vec<f32>], k: usize) -> (Vec<usize>, Vec<Vec<f32>>) {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut centroids: Vec<Vec<f32>> = vec![points[0].clone()];
+    let mut nearest: Vec<f32> = points.iter().map(|p| 1.0 - vector::dot(p, &points[0])).collect();
+    while centroids.len() < k {
+        let total: f32 = nearest.iter().map(|d| d.max(0.0).powi(2)).sum();
+        if total <= 0.0 {
+            break; // fewer distinct points than clusters
+        }
+        let mut target = rng.next_f32() * total;
+        let mut pick = points.len() - 1;
+        for (i, d) in nearest.iter().enumerate() {
+            target -= d.max(0.0).powi(2);
+            if target <= 0.0 {
+                pick = i;
+                break;
+            }
+        }
+        for (d, p) in nearest.iter_mut().zip(points) {
+            *d = d.min(1.0 - vector::dot(p, &points[pick]));
+        }
+        centroids.push(points[pick].clone());
+    }
+
+    let dims = points[0].len();
+    let mut assignment = vec![usize::MAX; points.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut moved = false;
+        for (a, p) in assignment.iter_mut().zip(points) {
+            let best = centroids
+                .iter()
+                .enumerate()
+                .map(|(c, centroid)| (c, vector::dot(p, centroid)))
+                .max_by(|x, y| x.1.total_cmp(&y.1))
+                .map(|(c, _)| c)
+                .unwrap_or(0);
+            moved |= *a != best;
+            *a = best;
+        }
+        if !moved {
+            break;
+        }
+        let mut sums = vec![vec![0.0f32; dims]; centroids.len()];
+        for (a, p) in assignment.iter().zip(points) {
+            for (s, x) in sums[*a].iter_mut().zip(p) {
+                *s += x;
+            }
+        }
+        // An emptied cluster keeps its old centroid.
+        for (centroid, mut sum) in centroids.iter_mut().zip(sums) {
+            if sum.iter().any(|s| *s != 0.0) {
+                vector::normalize(&mut sum);
+                *centroid = sum;
+            }
+        }
+    }
+    (assignment, centroids)
+}
+
+/// Cluster `(identifier, embedding)` pairs into `k` topics, largest first.
+///
+/// Embeddings are truncated to the shortest length present and normalized.
+pub fn cluster(items: Vec<(i64, Vec<f32>)>, k: usize) -> Vec<Topic> {
+    let Some(dims) = items.iter().map(|(_, v)| v.len()).min() else {
+        return Vec::new();
+    };
+    if dims == 0 || k == 0 {
+        return Vec::new();
+    }
+    let (ids, points): (Vec<i64>, Vec<Vec<f32>>) = items
+        .into_iter()
+        .map(|(id, mut v)| {
+            v.truncate(dims);
+            vector::normalize(&mut v);
+            (id, v)
+        })
+        .unzip();
+
+    let (assignment, centroids) = kmeans(&points, k.min(points.len()));
+    let mut members: Vec<Vec<(i64, f32)>> = vec![Vec::new(); centroids.len()];
+    for ((id, p), c) in ids.iter().zip(&points).zip(&assignment) {
+        members[*c].push((*id, vector::dot(p, &centroids[*c])));
+    }
+
+    let mut topics: Vec<Topic> = members
+        .into_iter()
+        .filter(|m| !m.is_empty())
+        .map(|mut m| {
+            m.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            Topic {
+                cohesion: m.iter().map(|(_, s)| s).sum::<f32>() / m.len() as f32,
+                members: m.into_iter().map(|(id, _)| id).collect(),
+            }
+        })
+        .collect();
+    topics.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then(a.members[0].cmp(&b.members[0])));
+    topics
+}
+
+/// Handle to topics being computed in the background.
+///
+/// Dropping the handle aborts the task.
+pub struct TopicBuild {
+    rx: std::sync::mpsc::Receiver<Result<Vec<Topic>, String>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl TopicBuild {
+    /// Start loading the `TOPIC_DIMS`-long embedding prefixes on a separate
+    /// connection and clustering them.
+    pub fn spawn(db: &Database) -> turso::Result<Self> {
+        let reader = db.reader()?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let task = tokio::spawn(async move {
+            let result = match reader.all_embeddings(TOPIC_DIMS).await {
+                Ok(items) => tokio::task::spawn_blocking(move || {
+                    let k = default_topic_count(items.len());
+                    cluster(items, k)
+                })
+                .await
+                .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(result);
+        });
+        Ok(Self { rx, task })
+    }
+
+    /// The finished topics (or error), once available.
+    pub fn poll(&self) -> Option<Result<Vec<Topic>, String>> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for TopicBuild {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_separates_themes() {
+        let mut items = Vec::new();
+        for i in 0..10 {
+            let jitter = i as f32 * 0.01;
+            items.push((i, vec![1.0, jitter, 0.0]));
+            items.push((100 + i, vec![0.0, jitter, 1.0]));
+        }
+        items.push((200, vec![0.0, 1.0, 0.0]));
+        let topics = cluster(items, 3);
+        assert_eq!(topics.len(), 3);
+        assert_eq!(topics[0].members.len(), 10);
+        assert_eq!(topics[1].members.len(), 10);
+        assert_eq!(topics[2].members, vec![200]);
+        assert!(topics[0].members.iter().all(|id| *id < 100));
+        assert!(topics[1].members.iter().all(|id| (100..200).contains(id)));
+        assert!(topics[0].cohesion > 0.99);
+    }
+
+    #[test]
+    fn test_cluster_more_topics_than_points() {
+        let topics = cluster(vec![(1, vec![1.0, 0.0]), (2, vec![1.0, 0.0])], 5);
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].members, vec![1, 2]);
+        assert!(cluster(Vec::new(), 5).is_empty());
+    }
+
+    #[test]
+    fn test_topic_label_and_count() {
+        let topic = Topic { members: vec![3, 1, 2], cohesion: 1.0 };
+        let label = topic.label(2, |id| (id != 1).then(|| format!("Title {}", id)));
+        assert_eq!(label, "Title 3 · Title 2");
+        assert_eq!(default_topic_count(0), 0);
+        assert_eq!(default_topic_count(50), 5);
+        assert_eq!(default_topic_count(100_000), MAX_TOPICS);
+    }
+}
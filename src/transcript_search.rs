@@ -0,0 +1,142 @@
+use crate::db::Database;
+use std::sync::mpsc::{Receiver, Sender};
+
+/// Number of transcript bodies fetched per query while scanning.
+const SCAN_BATCH: i64 = 200;
+
+/// Messages sent from the background scan to the TUI thread.
+#[derive(Debug, Clone)]
+pub enum ScanMessage {
+    /// Identifiers whose transcript matched in the latest batch
+    Matches(Vec<i64>),
+    /// Number of rows scanned so far
+    Progress { scanned: usize },
+    /// The scan reached the end of the table
+    Done,
+    /// The scan stopped with an error
+    Error(String),
+}
+
+/// Handle to a running transcript-body search.
+///
+/// Dropping the handle (or calling `cancel`) aborts the background task.
+pub struct TranscriptSearch {
+    pub scanned: usize,
+    pub total: usize,
+    pub hits: usize,
+    pub done: bool,
+    pub error: Option<String>,
+    rx: Receiver<ScanMessage>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl TranscriptSearch {
+    /// Start scanning all transcripts for `query` on a separate connection.
+    pub fn spawn(db: &Database, query: &str, total: usize) -> turso::Result<Self> {
+        let reader = db.reader()?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let terms: Vec<String> = query.to_lowercase().split_whitespace().map(String::from).collect();
+        let task = tokio::spawn(scan(reader, terms, tx));
+
+        Ok(Self {
+            scanned: 0,
+            total,
+            hits: 0,
+            done: false,
+            error: None,
+            rx,
+            task,
+        })
+    }
+
+    /// Drain pending messages, returning newly matched identifiers.
+    pub fn poll(&mut self) -> Vec<i64> {
+        let mut new_ids = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            match message {
+                ScanMessage::Matches(ids) => {
+                    self.hits += ids.len();
+                    new_ids.extend(ids);
+                }
+                ScanMessage::Progress { scanned } => self.scanned = scanned,
+                ScanMessage::Done => self.done = true,
+                ScanMessage::Error(e) => {
+                    self.error = Some(e);
+                    self.done = true;
+                }
+            }
+        }
+        new_ids
+    }
+
+    /// Fraction of rows scanned, in `0.0..=1.0`.
+    pub fn progress(&self) -> f64 {
+        if self.done || self.total == 0 {
+            1.0
+        } else {
+            (self.scanned as f64 / self.total as f64).min(1.0)
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for TranscriptSearch {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// True if every term occurs in the (already lowercased) text.
+pub fn matches_all_terms(text: &str, terms: &[String]) -> bool {
+    !terms.is_empty() && terms.iter().all(|t| text.contains(t.as_str()))
+}
+
+async fn scan(db: Database, terms: Vec<String>, tx: Sender<ScanMessage>) {
+    let mut after_id = i64::MIN;
+    let mut scanned = 0;
+
+    loop {
+        let batch = match db.scan_transcripts(after_id, SCAN_BATCH).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                let _ = tx.send(ScanMessage::Error(e.to_string()));
+                return;
+            }
+        };
+        let Some(&(last_id, _)) = batch.last() else {
+            let _ = tx.send(ScanMessage::Done);
+            return;
+        };
+
+        scanned += batch.len();
+        let ids: Vec<i64> = batch
+            .into_iter()
+            .filter(|(_, transcript)| matches_all_terms(&transcript.to_lowercase(), &terms))
+            .map(|(id, _)| id)
+            .collect();
+
+        // The receiver is gone once the query changes; stop quietly.
+        if (!ids.is_empty() && tx.send(ScanMessage::Matches(ids)).is_err())
+            || tx.send(ScanMessage::Progress { scanned }).is_err()
+        {
+            return;
+        }
+        after_id = last_id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_all_terms() {
+        let terms = vec!["rust".to_string(), "kernel".to_string()];
+        assert!(matches_all_terms("the linux kernel accepts rust", &terms));
+        assert!(!matches_all_terms("the linux kernel", &terms));
+        assert!(!matches_all_terms("anything", &[]));
+    }
+}
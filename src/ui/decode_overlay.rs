@@ -0,0 +1,68 @@
+//! Screen shown while an encrypted or compressed database is decoded, before the TUI starts.
+
+use crate::codec::{Phase, Progress};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Gauge, Paragraph};
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Render the decoding screen over the whole terminal.
+///
+/// `title` names the operation and `destination` where the plaintext goes
+/// (memory or a temporary file); `progress` is `None` until decoding starts.
+pub fn render(frame: &mut Frame, title: &str, destination: &str, progress: Option<Progress>) {
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Length(2),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+
+    let (status, detail) = match progress {
+        None => (format!("Preparing to decode {}...", destination), String::new()),
+        // scrypt reads nothing while it runs, so there is no fraction to show
+        Some(Progress { phase: Phase::KeyDerivation, .. }) => (
+            "Deriving key from passphrase...".to_string(),
+            "This takes a few seconds and is deliberately slow".to_string(),
+        ),
+        Some(progress) => (
+            format!("{} {}...", progress.phase.label(), destination),
+            format!("{} / {}", megabytes(progress.read), megabytes(progress.total)),
+        ),
+    };
+    frame.render_widget(Paragraph::new(status).alignment(Alignment::Center), chunks[1]);
+
+    let horizontal = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(20), Constraint::Percentage(60), Constraint::Percentage(20)])
+        .split(chunks[2]);
+    let ratio = match progress {
+        Some(p) if p.phase != Phase::KeyDerivation => p.ratio(),
+        _ => 0.0,
+    };
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(ratio);
+    frame.render_widget(gauge, horizontal[1]);
+
+    let detail = Paragraph::new(detail)
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    frame.render_widget(detail, chunks[3]);
+}
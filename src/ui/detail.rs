@@ -14,11 +14,11 @@ pub fn render(app: &App, frame: &mut Frame) {
         None => return,
     };
 
-    // Layout: header(5) + tabs(3) + content(min) + status(1)
+    // Layout: header(6) + tabs(3) + content(min) + status(1)
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(5),
+            Constraint::Length(6),
             Constraint::Length(3),
             Constraint::Min(5),
             Constraint::Length(1),
@@ -26,7 +26,7 @@ pub fn render(app: &App, frame: &mut Frame) {
         .split(area);
 
     // ── Metadata header ──
-    let meta_lines = vec![
+    let mut meta_lines = vec![
         Line::from(vec![
             Span::styled(" ID: ", Style::default().fg(Color::DarkGray)),
             Span::styled(
@@ -65,6 +65,29 @@ pub fn render(app: &App, frame: &mut Frame) {
         ]),
     ];
 
+    let annotation = app
+        .annotation_for(detail.identifier, &detail.original_source_link)
+        .cloned()
+        .unwrap_or_default();
+    let tags: Vec<String> = annotation.tags.iter().map(|t| format!("#{}", t)).collect();
+    meta_lines.push(Line::from(vec![
+        Span::styled(
+            if annotation.bookmarked { " ★ " } else { " ☆ " },
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled("Tags: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            if tags.is_empty() { "-".to_string() } else { tags.join(" ") },
+            Style::default().fg(Color::Magenta),
+        ),
+        Span::raw("   "),
+        Span::styled("Note: ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            if annotation.note.is_empty() { "-".to_string() } else { annotation.note.clone() },
+            Style::default().fg(Color::White),
+        ),
+    ]));
+
     let meta_block = Paragraph::new(meta_lines).block(
         Block::default()
             .borders(Borders::ALL)
@@ -105,19 +128,7 @@ pub fn render(app: &App, frame: &mut Frame) {
     frame.render_widget(tabs, chunks[1]);
 
     // ── Content area ──
-    let content_text = match app.detail_tab {
-        DetailTab::Summary => &detail.summary,
-        DetailTab::Transcript => &detail.transcript,
-        DetailTab::Timestamps => {
-            if !detail.timestamped_summary_in_youtube_format.is_empty() {
-                &detail.timestamped_summary_in_youtube_format
-            } else {
-                &detail.timestamps
-            }
-        }
-    };
-
-    let content = Paragraph::new(content_text.as_str())
+    let content = Paragraph::new(app.detail_tab.content(detail))
         .wrap(Wrap { trim: false })
         .scroll((app.detail_scroll, 0))
         .block(
@@ -168,6 +179,13 @@ pub fn render(app: &App, frame: &mut Frame) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(" Yank Link  "),
+        Span::styled(
+            "b/#/n",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" Bookmark/Tags/Note  "),
         Span::styled(
             "Esc",
             Style::default()
@@ -0,0 +1,144 @@
+use crate::app::{self, App};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let area = frame.area();
+
+    // Layout: header(3) + clusters(min) + status(1)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    // ── Header ──
+    let members: usize = app.duplicate_clusters.iter().map(|c| c.members.len()).sum();
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled(
+            " Duplicate Clusters ",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                " [{} clusters, {} entries, {} redundant]",
+                app.duplicate_clusters.len(),
+                members,
+                members - app.duplicate_clusters.len()
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(header, chunks[0]);
+
+    // ── Clusters ──
+    // Each cluster is a header row followed by its members; only members are selectable.
+    let mut items = Vec::new();
+    let mut selected_row = 0;
+    let mut member_index = 0;
+    for (n, cluster) in app.duplicate_clusters.iter().enumerate() {
+        let reasons: Vec<&str> = cluster.reasons.iter().map(|r| r.label()).collect();
+        let mut header_spans = vec![
+            Span::styled(
+                format!("Cluster {} ", n + 1),
+                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("· {} items · {}", cluster.members.len(), reasons.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        if let Some(d) = cluster.min_distance {
+            header_spans.push(Span::styled(
+                format!(" · min distance {:.3}", d),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        items.push(ListItem::new(Line::from(header_spans)));
+
+        for id in &cluster.members {
+            if member_index == app.duplicates_selected {
+                selected_row = items.len();
+            }
+            member_index += 1;
+
+            let (title, link, bookmarked) = match app.item(*id) {
+                Some(item) => (
+                    app::get_display_title(&item.summary),
+                    item.original_source_link.clone(),
+                    item.bookmarked,
+                ),
+                None => (String::new(), String::new(), false),
+            };
+            let mut line_spans = vec![Span::styled(
+                format!("  {:>5} ", id),
+                Style::default().fg(Color::DarkGray),
+            )];
+            if bookmarked {
+                line_spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+            }
+            line_spans.push(Span::raw(super::list::truncate_str(
+                &title,
+                (area.width as usize).saturating_sub(60),
+            )));
+            line_spans.push(Span::styled(
+                format!("  {}", link),
+                Style::default().fg(Color::Blue),
+            ));
+            items.push(ListItem::new(Line::from(line_spans)));
+        }
+    }
+
+    let list_widget = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(" Same source link or near-identical embedding ")
+                .title_bottom(
+                    Line::from(format!(" {} of {} ", (app.duplicates_selected + 1).min(members), members))
+                        .alignment(Alignment::Right),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+
+    let mut list_state = ListState::default();
+    if members > 0 {
+        list_state.select(Some(selected_row));
+    }
+    frame.render_stateful_widget(list_widget, chunks[1], &mut list_state);
+
+    // ── Status bar ──
+    let key_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let status_line = Line::from(vec![
+        Span::styled(" ↑↓/PgUpDn", key_style),
+        Span::raw(" Navigate  "),
+        Span::styled("Enter", key_style),
+        Span::raw(" Detail  "),
+        Span::styled("b", key_style),
+        Span::raw(" Bookmark  "),
+        Span::styled("Esc", key_style),
+        Span::raw(" Back  "),
+        Span::styled(&app.status_msg, Style::default().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(status_line), chunks[2]);
+}
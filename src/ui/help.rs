@@ -45,10 +45,42 @@ pub fn render(frame: &mut Frame) {
             Span::styled("    /         ", Style::default().fg(Color::Yellow)),
             Span::raw("Start filtering (type to search)"),
         ]),
+        Line::from(vec![
+            Span::styled("    F         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Ranked full-text search (incl. transcripts)"),
+        ]),
+        Line::from(vec![
+            Span::styled("    t         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Toggle streaming search inside transcripts"),
+        ]),
+        Line::from(vec![
+            Span::styled("    b / # / n ", Style::default().fg(Color::Yellow)),
+            Span::raw("Toggle bookmark / edit tags / edit note"),
+        ]),
         Line::from(vec![
             Span::styled("    s         ", Style::default().fg(Color::Yellow)),
             Span::raw("Find similar transcripts (vector search)"),
         ]),
+        Line::from(vec![
+            Span::styled("    D         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Show near-duplicate clusters"),
+        ]),
+        Line::from(vec![
+            Span::styled("    T         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Browse topics (embedding clusters)"),
+        ]),
+        Line::from(vec![
+            Span::styled("    C         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Statistics per model/host/language/month"),
+        ]),
+        Line::from(vec![
+            Span::styled("    E         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Export current list (.json/.jsonl/.csv/.md)"),
+        ]),
+        Line::from(vec![
+            Span::styled("    m / S     ", Style::default().fg(Color::Yellow)),
+            Span::raw("Mark entry / similar to centroid of marked"),
+        ]),
         Line::from(vec![
             Span::styled("    g/G       ", Style::default().fg(Color::Yellow)),
             Span::raw("Jump to first/last page"),
@@ -77,6 +109,10 @@ pub fn render(frame: &mut Frame) {
             Span::styled("    y         ", Style::default().fg(Color::Yellow)),
             Span::raw("Copy source link to clipboard"),
         ]),
+        Line::from(vec![
+            Span::styled("    b / # / n ", Style::default().fg(Color::Yellow)),
+            Span::raw("Toggle bookmark / edit tags / edit note"),
+        ]),
         Line::from(""),
         Line::from(vec![
             Span::styled("  Similar View", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
@@ -89,6 +125,22 @@ pub fn render(frame: &mut Frame) {
             Span::styled("    Enter     ", Style::default().fg(Color::Yellow)),
             Span::raw("Open selected result"),
         ]),
+        Line::from(vec![
+            Span::styled("    l         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Load more results"),
+        ]),
+        Line::from(vec![
+            Span::styled("    M / D     ", Style::default().fg(Color::Yellow)),
+            Span::raw("Cycle metric (cosine/L2/dot) / embedding dims"),
+        ]),
+        Line::from(vec![
+            Span::styled("    T         ", Style::default().fg(Color::Yellow)),
+            Span::raw("Cut off at selected distance / clear cut-off"),
+        ]),
+        Line::from(vec![
+            Span::styled("    I / R     ", Style::default().fg(Color::Yellow)),
+            Span::raw("Toggle ANN index / exact re-ranking"),
+        ]),
         Line::from(""),
     ];
 
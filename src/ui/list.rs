@@ -35,12 +35,37 @@ pub fn render(app: &App, frame: &mut Frame) {
         app.filtered_indices.len()
     };
     
-    let header_text = format!(
+    let mut header_spans = vec![Span::raw(format!(
         " Transcript Explorer   [{} entries in {} groups]",
         entries_count,
         app.grouped_items.len()
-    );
-    let header = Paragraph::new(header_text)
+    ))];
+    if !app.marked.is_empty() {
+        header_spans.push(Span::styled(
+            format!("   [{} marked]", app.marked.len()),
+            Style::default().fg(Color::LightBlue),
+        ));
+    }
+    if let Some(ref search) = app.transcript_search {
+        let state = if search.error.is_some() {
+            "failed".to_string()
+        } else if search.done {
+            "done".to_string()
+        } else {
+            format!("{:>3.0}%", search.progress() * 100.0)
+        };
+        header_spans.push(Span::styled(
+            format!("   [transcripts {} · {} hits]", state, search.hits),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if let Some(ref build) = app.fulltext_build {
+        header_spans.push(Span::styled(
+            format!("   [full-text index {:>3.0}%]", build.progress() * 100.0),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let header = Paragraph::new(Line::from(header_spans))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -57,12 +82,13 @@ pub fn render(app: &App, frame: &mut Frame) {
     // ── Filter bar ──
     let filter_style = match app.input_mode {
         InputMode::Editing => Style::default().fg(Color::Yellow),
-        InputMode::Normal => Style::default().fg(Color::DarkGray),
+        _ => Style::default().fg(Color::DarkGray),
     };
-    let filter_label = if app.input_mode == InputMode::Editing {
-        " 🔍 Filter (Esc to finish): "
-    } else {
-        " 🔍 Filter (/): "
+    let filter_label = match (app.input_mode == InputMode::Editing, app.search_transcripts) {
+        (true, false) => " 🔍 Filter (Esc to finish): ",
+        (true, true) => " 🔍 Filter + transcripts (Esc to finish): ",
+        (false, false) => " 🔍 Filter (/): ",
+        (false, true) => " 🔍 Filter + transcripts (/, t to toggle): ",
     };
     let filter_text = format!("{}{}", filter_label, app.filter);
     let filter_bar = Paragraph::new(filter_text)
@@ -108,6 +134,14 @@ pub fn render(app: &App, frame: &mut Frame) {
             ),
         ];
 
+        if app.marked.contains(&first.identifier) {
+            line_spans.push(Span::styled("◆ ", Style::default().fg(Color::LightBlue)));
+        }
+
+        if first.bookmarked {
+            line_spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+        }
+
         if !group.expanded && group.items.len() > 1 {
             line_spans.push(Span::styled(
                 format!("[+{}] ", group.items.len() - 1),
@@ -121,6 +155,14 @@ pub fn render(app: &App, frame: &mut Frame) {
         }
 
         line_spans.push(Span::raw(truncate_str(&title, (area.width as usize).saturating_sub(40))));
+
+        if !first.tags.is_empty() {
+            let tags: Vec<String> = first.tags.iter().map(|t| format!("#{}", t)).collect();
+            line_spans.push(Span::styled(
+                format!("  {}", tags.join(" ")),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
         
         line_spans.push(Span::styled(
             format!("  ${:.3}", first.cost),
@@ -1,11 +1,16 @@
-mod list;
+pub mod list;
 mod detail;
 pub mod similar;
 pub mod preview;
 pub mod filters;
 mod help;
+mod duplicates;
+mod topics;
+mod stats;
+mod prompt;
 pub mod update_overlay;
 pub mod password_overlay;
+pub mod decode_overlay;
 
 use crate::app::App;
 use ratatui::Frame;
@@ -17,6 +22,14 @@ pub fn render(app: &App, frame: &mut Frame) {
         crate::app::View::Detail => detail::render(app, frame),
         crate::app::View::Similar => similar::render(app, frame),
         crate::app::View::Filters => filters::render(app, frame),
+        crate::app::View::Duplicates => duplicates::render(app, frame),
+        crate::app::View::Topics => topics::render(app, frame),
+        crate::app::View::Stats => stats::render(app, frame),
+    }
+
+    // Render the single-line prompt while editing tags, a note or an export path
+    if app.input_mode.is_prompt() {
+        prompt::render(app, frame);
     }
 
     // Render update overlay if visible
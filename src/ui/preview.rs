@@ -9,22 +9,27 @@ use ratatui::{
 };
 use chrono::{DateTime, NaiveDateTime};
 
-pub fn render_preview(_app: &App, frame: &mut Frame, area: Rect, item: &TranscriptListItem, similarity: Option<f64>) {
-    let sim_info = if let Some(dist) = similarity {
-        let similarity_val = 1.0 - dist;
-        let sim_color = if similarity_val > 0.90 {
-            Color::Green
-        } else if similarity_val > 0.80 {
-            Color::Yellow
-        } else {
-            Color::Red
-        };
-        vec![
-            Span::styled(" Similarity: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{:.1}%", similarity_val * 100.0), Style::default().fg(sim_color).add_modifier(Modifier::BOLD)),
-        ]
-    } else {
-        vec![]
+pub fn render_preview(app: &App, frame: &mut Frame, area: Rect, item: &TranscriptListItem, similarity: Option<f64>) {
+    let metric = app.settings.similarity.metric;
+    let sim_info = match similarity.map(|dist| (dist, metric.similarity(dist))) {
+        Some((_, Some(similarity_val))) => {
+            let sim_color = if similarity_val > 0.90 {
+                Color::Green
+            } else if similarity_val > 0.80 {
+                Color::Yellow
+            } else {
+                Color::Red
+            };
+            vec![
+                Span::styled(" Similarity: ", Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:.1}%", similarity_val * 100.0), Style::default().fg(sim_color).add_modifier(Modifier::BOLD)),
+            ]
+        }
+        Some((dist, None)) => vec![
+            Span::styled(format!(" Distance ({}): ", metric.label()), Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{:.3}", dist), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ],
+        None => vec![],
     };
 
     // Duration calculation
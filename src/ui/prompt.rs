@@ -0,0 +1,68 @@
+use crate::app::{App, InputMode};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Single-line prompt for editing the tags or note of the selected transcript,
+/// or the file name of an export.
+pub fn render(app: &App, frame: &mut Frame) {
+    let area = centered_rect(60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (title, hint) = match app.input_mode {
+        InputMode::EditingTags => (" Edit Tags ", "Comma or space separated, empty to clear"),
+        InputMode::EditingExportPath if app.export_overwrite_pending() => {
+            (" Export List ", "File exists: Enter overwrites it, or change the name")
+        }
+        InputMode::EditingExportPath => (
+            " Export List ",
+            "File name; the extension (.json, .jsonl, .csv, .md) picks the format",
+        ),
+        _ => (" Edit Note ", "Empty to clear"),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(title)
+        .title_bottom(Line::from(" Enter: Save | Esc: Cancel ").style(Style::default().fg(Color::DarkGray)));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let lines = vec![
+        Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Yellow)),
+            Span::raw(app.prompt_buffer.as_str()),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let cursor_x = inner.x + 2 + app.prompt_buffer.chars().count() as u16;
+    frame.set_cursor_position((cursor_x.min(inner.right().saturating_sub(1)), inner.y + 1));
+}
+
+/// A horizontally centered box, four rows high, in the middle of the screen.
+fn centered_rect(percent_x: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(4),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
@@ -16,11 +16,11 @@ pub fn render(app: &App, frame: &mut Frame) {
         Constraint::Length(10)
     };
 
-    // Layout: header(4) + results(min) + preview(?) + status(1)
+    // Layout: header(5) + results(min) + preview(?) + status(1)
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4),
+            Constraint::Length(5),
             Constraint::Min(5),
             preview_height,
             Constraint::Length(1),
@@ -34,14 +34,20 @@ pub fn render(app: &App, frame: &mut Frame) {
         (area.width as usize).saturating_sub(25),
     );
     
+    let settings = &app.settings.similarity;
+    let source_label = if app.similar_seed_ids.is_empty() {
+        (" Similar to ID ", app.similar_source_id.to_string())
+    } else {
+        (" Similar to centroid of ", format!("{} items", app.similar_seed_ids.len()))
+    };
     let header_lines = vec![
         Line::from(vec![
             Span::styled(
-                " Similar to ID ",
+                source_label.0,
                 Style::default().fg(Color::DarkGray),
             ),
             Span::styled(
-                app.similar_source_id.to_string(),
+                source_label.1,
                 Style::default()
                     .fg(Color::White)
                     .add_modifier(Modifier::BOLD),
@@ -54,6 +60,21 @@ pub fn render(app: &App, frame: &mut Frame) {
                 Style::default().fg(Color::Cyan),
             ),
         ]),
+        Line::from(vec![
+            Span::styled(" Metric (M) ", Style::default().fg(Color::DarkGray)),
+            Span::styled(settings.metric.label(), Style::default().fg(Color::Yellow)),
+            Span::styled("  Dims (D) ", Style::default().fg(Color::DarkGray)),
+            Span::styled(settings.dims_label(), Style::default().fg(Color::Yellow)),
+            Span::styled("  Cut-off (T) ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                settings.max_distance.map(|d| format!("{:.3}", d)).unwrap_or_else(|| "none".to_string()),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled("  Index (I) ", Style::default().fg(Color::DarkGray)),
+            Span::styled(app.ann_status(), Style::default().fg(Color::Yellow)),
+            Span::styled("  Rerank (R) ", Style::default().fg(Color::DarkGray)),
+            Span::styled(if settings.exact_rerank { "on" } else { "off" }, Style::default().fg(Color::Yellow)),
+        ]),
     ];
     let header = Paragraph::new(header_lines).block(
         Block::default()
@@ -76,14 +97,7 @@ pub fn render(app: &App, frame: &mut Frame) {
             _selected_result = Some(result.clone());
         }
 
-        let similarity = 1.0 - result.distance;
-        let sim_color = if similarity > 0.90 {
-            Color::Green
-        } else if similarity > 0.80 {
-            Color::Yellow
-        } else {
-            Color::Red
-        };
+        let (score, sim_color) = score_span(app, result.distance);
 
         let title = app::get_display_title(&result.summary);
         
@@ -93,7 +107,7 @@ pub fn render(app: &App, frame: &mut Frame) {
                 Style::default().fg(Color::DarkGray),
             ),
             Span::styled(
-                format!("{:.3} ", similarity),
+                format!("{} ", score),
                 Style::default().fg(sim_color).add_modifier(Modifier::BOLD),
             ),
             Span::styled(
@@ -123,11 +137,11 @@ pub fn render(app: &App, frame: &mut Frame) {
 
         if group.expanded {
             for sub_item in group.items.iter().skip(1) {
-                let sub_similarity = 1.0 - sub_item.distance;
+                let (sub_score, _) = score_span(app, sub_item.distance);
                 let sub_line = Line::from(vec![
                     Span::raw("        "), // padding
                     Span::styled(
-                        format!("{:.3} ", sub_similarity),
+                        format!("{} ", sub_score),
                         Style::default().fg(Color::DarkGray),
                     ),
                     Span::styled(
@@ -141,13 +155,13 @@ pub fn render(app: &App, frame: &mut Frame) {
         }
     }
 
-    let result_count = format!(" {} groups ({} total results) ", app.grouped_similar_results.len(), app.similar_results.len());
+    let result_count = format!(" {} groups ({} total results, l: load more) ", app.grouped_similar_results.len(), app.similar_results.len());
     let list_widget = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::DarkGray))
-                .title(" Results (sorted by similarity) ")
+                .title(format!(" Results (sorted by {} distance) ", app.settings.similarity.metric.label()))
                 .title_bottom(Line::from(result_count).alignment(Alignment::Right)),
         )
         .highlight_style(
@@ -175,10 +189,13 @@ pub fn render(app: &App, frame: &mut Frame) {
                 has_embedding: true,
                 model: res.model.clone(),
                 original_source_link: res.original_source_link.clone(),
+                output_language: String::new(),
                 summary_input_tokens: res.summary_input_tokens,
                 summary_output_tokens: res.summary_output_tokens,
                 summary_timestamp_start: res.summary_timestamp_start.clone(),
                 summary_timestamp_end: res.summary_timestamp_end.clone(),
+                bookmarked: false,
+                tags: Vec::new(),
             };
             super::preview::render_preview(app, frame, chunks[2], &item, Some(res.distance));
         }
@@ -237,3 +254,20 @@ pub fn render(app: &App, frame: &mut Frame) {
     let status_bar = Paragraph::new(status_line);
     frame.render_widget(status_bar, chunks[3]);
 }
+
+/// Score shown for a result: similarity for cosine, the raw distance otherwise.
+fn score_span(app: &App, distance: f64) -> (String, Color) {
+    match app.settings.similarity.metric.similarity(distance) {
+        Some(similarity) => {
+            let color = if similarity > 0.90 {
+                Color::Green
+            } else if similarity > 0.80 {
+                Color::Yellow
+            } else {
+                Color::Red
+            };
+            (format!("{:.3}", similarity), color)
+        }
+        None => (format!("{:>8.3}", distance), Color::Yellow),
+    }
+}
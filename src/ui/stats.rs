@@ -0,0 +1,150 @@
+use crate::app::App;
+use crate::stats::GroupBy;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs},
+};
+
+/// Width of the group key column.
+const KEY_WIDTH: usize = 28;
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let area = frame.area();
+
+    // Layout: header(3) + group tabs(3) + table(min) + status(1)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    // ── Header ──
+    let entries: usize = app.stats_groups.iter().map(|g| g.count).sum();
+    let total_cost: f64 = app.stats_groups.iter().map(|g| g.total_cost).sum();
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled(
+            " Statistics ",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" [{} entries, total cost ${:.4}]", entries, total_cost),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(header, chunks[0]);
+
+    // ── Group tabs ──
+    let tab_titles: Vec<Line> = GroupBy::ALL
+        .iter()
+        .map(|by| {
+            let style = if *by == app.stats_group_by {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(Span::styled(by.label(), style))
+        })
+        .collect();
+    let tab_index = GroupBy::ALL
+        .iter()
+        .position(|by| *by == app.stats_group_by)
+        .unwrap_or(0);
+    let tabs = Tabs::new(tab_titles)
+        .select(tab_index)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(" Group by [Tab] "),
+        )
+        .highlight_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(tabs, chunks[1]);
+
+    // ── Table ──
+    let column_header = format!(
+        "  {:<w$} {:>6} {:>11} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        app.stats_group_by.label().to_uppercase(),
+        "COUNT",
+        "TOTAL COST",
+        "MEAN",
+        "MEDIAN",
+        "MAD",
+        "P95",
+        "IN TOK",
+        "OUT TOK",
+        w = KEY_WIDTH
+    );
+    let items: Vec<ListItem> = app
+        .stats_groups
+        .iter()
+        .map(|g| {
+            let share = if total_cost > 0.0 { g.total_cost / total_cost * 100.0 } else { 0.0 };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<w$}", super::list::truncate_str(&g.key, KEY_WIDTH), w = KEY_WIDTH)),
+                Span::styled(format!(" {:>6}", g.count), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!(" {:>11.4}", g.total_cost), Style::default().fg(Color::Yellow)),
+                Span::raw(format!(
+                    " {:>9.4} {:>9.4} {:>9.4} {:>9.4} {:>9.0} {:>9.0}",
+                    g.cost.mean, g.cost.median, g.cost.mad, g.cost.p95, g.input_tokens.mean, g.output_tokens.mean
+                )),
+                Span::styled(format!("  {:>5.1}%", share), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let list_widget = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(column_header)
+                .title_bottom(
+                    Line::from(format!(
+                        " {} of {} ",
+                        (app.stats_selected + 1).min(app.stats_groups.len()),
+                        app.stats_groups.len()
+                    ))
+                    .alignment(Alignment::Right),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+
+    let mut list_state = ListState::default();
+    if !app.stats_groups.is_empty() {
+        list_state.select(Some(app.stats_selected));
+    }
+    frame.render_stateful_widget(list_widget, chunks[2], &mut list_state);
+
+    // ── Status bar ──
+    let key_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let status_line = Line::from(vec![
+        Span::styled(" ↑↓/PgUpDn", key_style),
+        Span::raw(" Navigate  "),
+        Span::styled("Tab", key_style),
+        Span::raw(" Group by  "),
+        Span::styled("Esc", key_style),
+        Span::raw(" Back  "),
+        Span::styled("Statistics of the filtered list; costs in $", Style::default().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(status_line), chunks[3]);
+}
@@ -0,0 +1,142 @@
+use crate::app::{self, App};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let area = frame.area();
+
+    // Layout: header(3) + topics or members(min) + status(1)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    // ── Header ──
+    let open = app.topic_open.and_then(|t| app.topics.get(t).map(|topic| (t, topic)));
+    let mut header_spans = vec![Span::styled(
+        " Topics ",
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )];
+    match open {
+        Some((t, topic)) => header_spans.push(Span::styled(
+            format!(
+                " › {} [{} entries, cohesion {:.2}]",
+                super::list::truncate_str(&app.topic_labels[t], (area.width as usize).saturating_sub(50)),
+                topic.members.len(),
+                topic.cohesion
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+        None if app.topics_pending() => header_spans.push(Span::styled(
+            " clustering embeddings...",
+            Style::default().fg(Color::Yellow),
+        )),
+        None => header_spans.push(Span::styled(
+            format!(" [{} topics over {} embeddings]", app.topics.len(), app.topics.iter().map(|t| t.members.len()).sum::<usize>()),
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+    let header = Paragraph::new(Line::from(header_spans)).block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(header, chunks[0]);
+
+    // ── Topics or members ──
+    let (items, selected, total, title): (Vec<ListItem>, usize, usize, &str) = match open {
+        Some((_, topic)) => {
+            let items = topic
+                .members
+                .iter()
+                .map(|id| {
+                    let (title, bookmarked) = match app.item(*id) {
+                        Some(item) => (app::get_display_title(&item.summary), item.bookmarked),
+                        None => (String::new(), false),
+                    };
+                    let mut spans = vec![Span::styled(format!("{:>6} ", id), Style::default().fg(Color::DarkGray))];
+                    if bookmarked {
+                        spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+                    }
+                    spans.push(Span::raw(super::list::truncate_str(&title, (area.width as usize).saturating_sub(14))));
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+            (items, app.topic_member_selected, topic.members.len(), " Members, most representative first ")
+        }
+        None => {
+            let items = app
+                .topics
+                .iter()
+                .zip(&app.topic_labels)
+                .map(|(topic, label)| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:>6} ", topic.members.len()),
+                            Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw(super::list::truncate_str(label, (area.width as usize).saturating_sub(14))),
+                    ]))
+                })
+                .collect();
+            (items, app.topics_selected, app.topics.len(), " Topics, largest first ")
+        }
+    };
+
+    let list_widget = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray))
+                .title(title)
+                .title_bottom(
+                    Line::from(format!(" {} of {} ", (selected + 1).min(total), total))
+                        .alignment(Alignment::Right),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+
+    let mut list_state = ListState::default();
+    if total > 0 {
+        list_state.select(Some(selected));
+    }
+    frame.render_stateful_widget(list_widget, chunks[1], &mut list_state);
+
+    // ── Status bar ──
+    let key_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let mut status_spans = vec![
+        Span::styled(" ↑↓/PgUpDn", key_style),
+        Span::raw(" Navigate  "),
+        Span::styled("Enter", key_style),
+    ];
+    if open.is_some() {
+        status_spans.extend([
+            Span::raw(" Detail  "),
+            Span::styled("b", key_style),
+            Span::raw(" Bookmark  "),
+        ]);
+    } else {
+        status_spans.push(Span::raw(" Members  "));
+    }
+    status_spans.extend([
+        Span::styled("Esc", key_style),
+        Span::raw(" Back  "),
+        Span::styled(&app.status_msg, Style::default().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(Line::from(status_spans)), chunks[2]);
+}
@@ -0,0 +1,161 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Distance function used for similarity search. Lower is always closer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    #[default]
+    Cosine,
+    L2,
+    /// Negated dot product, so that ordering by distance still works.
+    Dot,
+}
+
+impl Metric {
+    pub fn sql_function(self) -> &'static str {
+        match self {
+            Metric::Cosine => "vector_distance_cos",
+            Metric::L2 => "vector_distance_l2",
+            Metric::Dot => "vector_distance_dot",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Cosine => "cosine",
+            Metric::L2 => "L2",
+            Metric::Dot => "dot",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Metric::Cosine => Metric::L2,
+            Metric::L2 => Metric::Dot,
+            Metric::Dot => Metric::Cosine,
+        }
+    }
+
+    /// Whether `distance` can serve as a cut-off: it must be finite, and
+    /// non-negative unless the metric is the (negated) dot product.
+    pub fn valid_cutoff(self, distance: f64) -> bool {
+        distance.is_finite() && (distance >= 0.0 || self == Metric::Dot)
+    }
+
+    /// Similarity in `0.0..=1.0` for display; only cosine distance maps onto one.
+    pub fn similarity(self, distance: f64) -> Option<f64> {
+        match self {
+            Metric::Cosine => Some(1.0 - distance),
+            Metric::L2 | Metric::Dot => None,
+        }
+    }
+}
+
+/// Decode an embedding BLOB (little-endian float32) into at most `dims` values.
+pub fn decode_embedding(blob: &[u8], dims: usize) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .take(dims)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Encode a vector as a float32 BLOB that Turso's vector functions accept.
+pub fn encode_embedding(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Dot product over the common prefix of `a` and `b`.
+///
+/// Accumulates in eight independent lanes so the compiler can vectorize it;
+/// this is the inner loop of the ANN index.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    let (a, b) = (&a[..n], &b[..n]);
+    let mut lanes = [0.0f32; 8];
+    let mut chunks_a = a.chunks_exact(8);
+    let mut chunks_b = b.chunks_exact(8);
+    for (ca, cb) in (&mut chunks_a).zip(&mut chunks_b) {
+        for i in 0..8 {
+            lanes[i] += ca[i] * cb[i];
+        }
+    }
+    let tail: f32 = chunks_a.remainder().iter().zip(chunks_b.remainder()).map(|(x, y)| x * y).sum();
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// Scale a vector to unit length in place. Zero vectors are left unchanged.
+pub fn normalize(vector: &mut [f32]) {
+    let norm = dot(vector, vector).sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Mean of the unit-normalized vectors, so every seed pulls with equal weight.
+///
+/// Vectors are truncated to the shortest length present (Matryoshka prefixes
+/// stay comparable). Returns `None` if there is nothing to average.
+pub fn centroid(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dims = vectors.iter().map(|v| v.len()).min()?;
+    if dims == 0 {
+        return None;
+    }
+    let mut sum = vec![0.0f32; dims];
+    for v in vectors {
+        let mut v = v[..dims].to_vec();
+        normalize(&mut v);
+        for (s, x) in sum.iter_mut().zip(v) {
+            *s += x;
+        }
+    }
+    let n = vectors.len() as f32;
+    sum.iter_mut().for_each(|s| *s /= n);
+    Some(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_round_trip_and_truncation() {
+        let v = vec![1.0, -2.5, 3.25, 4.0];
+        let blob = encode_embedding(&v);
+        assert_eq!(decode_embedding(&blob, 10), v);
+        assert_eq!(decode_embedding(&blob, 2), vec![1.0, -2.5]);
+    }
+
+    #[test]
+    fn test_dot_matches_naive_sum() {
+        let a: Vec<f32> = (0..19).map(|i| i as f32 * 0.5).collect();
+        let b: Vec<f32> = (0..19).map(|i| 1.0 - i as f32 * 0.1).collect();
+        let naive: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        assert!((dot(&a, &b) - naive).abs() < 1e-4);
+        assert_eq!(dot(&a[..3], &b), dot(&a[..3], &b[..3]));
+    }
+
+    #[test]
+    fn test_valid_cutoff() {
+        assert!(Metric::Cosine.valid_cutoff(0.3));
+        assert!(!Metric::Cosine.valid_cutoff(-0.1));
+        assert!(!Metric::L2.valid_cutoff(f64::NAN));
+        assert!(!Metric::L2.valid_cutoff(f64::INFINITY));
+        assert!(Metric::Dot.valid_cutoff(-12.5));
+        assert!(!Metric::Dot.valid_cutoff(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn test_centroid_weights_seeds_equally() {
+        let c = centroid(&[vec![10.0, 0.0], vec![0.0, 1.0]]).unwrap();
+        assert!((c[0] - 0.5).abs() < 1e-6);
+        assert!((c[1] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_centroid_truncates_to_shortest() {
+        let c = centroid(&[vec![1.0, 0.0, 5.0], vec![1.0, 0.0]]).unwrap();
+        assert_eq!(c.len(), 2);
+        assert!(centroid(&[]).is_none());
+    }
+}